@@ -2,12 +2,107 @@
 pragma solidity ^0.8.16;
 
 interface IArbiProofSimulator {
-    function initiate_dispute(bytes32 tx_hash, address defender) external payable returns (bytes32);
-    function submit_bisection_challenge(bytes32 dispute_id, uint256 bisection_point, bytes32 claim_hash) external;
+    struct ChallengeRound {
+        uint256 round;
+        uint256 bisectionPoint;
+        bytes32 challengerClaim;
+        bytes32 defenderResponse;
+        uint8 status;
+    }
+
+    function get_genesis_state_root() external view returns (bytes32);
+    function post_assertion(bytes32 parent_id, bytes32 state_root) external payable returns (bytes32);
+    function confirm_assertion(bytes32 assertion_id) external;
+    function reject_assertion(bytes32 assertion_id) external;
+    function initiate_dispute(bytes32 assertion_id, uint256 trace_length, bytes32 end_state_hash, bytes32 trace_root, bytes32[] calldata start_proof, bytes32[] calldata end_proof) external payable returns (bytes32);
+    function initiate_dispute_with_config(bytes32 assertion_id, uint256 trace_length, bytes32 end_state_hash, bytes32 trace_root, bytes32[] calldata start_proof, bytes32[] calldata end_proof, uint256 clock_budget, uint256 bond_window) external payable returns (bytes32);
+    function initiate_history_dispute(bytes32 assertion_id, uint256 trace_length, bytes32 end_root, bytes32 end_state, bytes32[] calldata end_expansion) external payable returns (bytes32);
+    function post_defender_bond(bytes32 dispute_id, bytes32 trace_root, bytes32[] calldata start_proof, bytes32[] calldata end_proof) external payable;
+    function post_history_defender_bond(bytes32 dispute_id, bytes32 end_root, bytes32[] calldata end_expansion) external payable;
+    function submit_bisection_challenge(bytes32 dispute_id, uint256 bisection_point, bytes32 claim_hash, bytes32[] calldata claim_proof, bytes32[] calldata claim_expansion) external;
+    function submit_defender_response(bytes32 dispute_id, uint256 round, bytes32 response_hash, bytes32[] calldata response_proof, bytes32[] calldata response_expansion) external;
+    function submit_one_step_proof(bytes32 dispute_id, bytes calldata pre_state, bytes calldata instruction, bytes32[] calldata proof, bytes32[] calldata history_expansion) external returns (address);
+    function compute_one_step(bytes calldata pre_state, bytes calldata instruction, bytes32[] calldata proof) external pure returns (bytes32);
     function resolve_dispute(bytes32 dispute_id) external;
-    function get_dispute(bytes32 dispute_id) external view returns (string memory);
+    function claim_timeout(bytes32 dispute_id) external returns (address);
+    function cancel_dispute(bytes32 dispute_id) external;
+    function settle_mutually(bytes32 dispute_id, address winner, uint256 deadline, bytes calldata challenger_signature, bytes calldata defender_signature) external;
+    function settlement_digest(bytes32 dispute_id, address winner, uint256 deadline) external view returns (bytes32);
+    function get_dispute(bytes32 dispute_id) external view returns (
+        address challenger,
+        address defender,
+        uint8 status,
+        uint256 current_round,
+        uint256 total_rounds,
+        uint256 timestamp,
+        bytes32 assertion_id,
+        uint256 range_start,
+        uint256 range_end,
+        bytes32 start_hash,
+        bytes32 challenger_end_hash,
+        bytes32 defender_end_hash,
+        uint256 challenger_stake,
+        uint256 defender_stake,
+        uint256 bond_deadline,
+        uint256 clock_budget
+    );
+    function get_challenge_round(bytes32 dispute_id, uint256 round) external view returns (
+        uint256 round_index,
+        uint256 bisection_point,
+        bytes32 challenger_claim,
+        bytes32 defender_response,
+        uint8 status
+    );
+    function get_challenge_rounds(bytes32 dispute_id) external view returns (ChallengeRound[] memory);
+    function get_dispute_range(bytes32 dispute_id) external view returns (uint256, uint256);
+    function get_dispute_claims(bytes32 dispute_id) external view returns (bytes32, bytes32, bytes32, bytes32, bytes32);
+    function get_dispute_mode(bytes32 dispute_id) external view returns (uint8);
     function get_challenge_rounds_count(bytes32 dispute_id) external view returns (uint256);
+    function get_assertion(bytes32 assertion_id) external view returns (address, bytes32, bytes32, uint256, uint256, uint8, uint256);
+    function compute_assertion_id(bytes32 parent_id, bytes32 state_root, address proposer) external view returns (bytes32);
+    function assertion_exists(bytes32 assertion_id) external view returns (bool);
+    function get_open_disputes(bytes32 assertion_id) external view returns (bytes32[] memory);
+    function compute_dispute_id(bytes32 assertion_id, address challenger) external view returns (bytes32);
     function dispute_exists(bytes32 dispute_id) external view returns (bool);
+    function get_dispute_count() external view returns (uint256);
+    function get_disputes(uint256 offset, uint256 limit) external view returns (bytes32[] memory);
+    function get_challenger_dispute_count(address challenger) external view returns (uint256);
+    function get_challenger_disputes(address challenger, uint256 offset, uint256 limit) external view returns (bytes32[] memory);
+    function get_defender_dispute_count(address defender) external view returns (uint256);
+    function get_defender_disputes(address defender, uint256 offset, uint256 limit) external view returns (bytes32[] memory);
+    function get_status_counts() external view returns (uint256, uint256, uint256);
+    function get_dispute_stakes(bytes32 dispute_id) external view returns (uint256, uint256);
+    function get_dispute_config(bytes32 dispute_id) external view returns (uint256, uint256, uint256);
+    function get_dispute_outcome(bytes32 dispute_id) external view returns (uint8, uint8, address);
+    function get_dispute_clocks(bytes32 dispute_id) external view returns (uint256, uint256, address);
+    function set_protocol_fee(uint256 fee_bps, address recipient) external;
+    function get_protocol_fee() external view returns (uint256, address);
+    function set_stake(uint256 stake) external;
+    function set_challenge_period(uint256 challenge_period) external;
+    function set_bond_window(uint256 bond_window) external;
+    function set_max_rounds(uint256 max_rounds) external;
+    function set_clock_budget(uint256 clock_budget) external;
+    function set_assertion_bond(uint256 assertion_bond) external;
+    function set_config_bounds(uint8 parameter, uint256 min, uint256 max) external;
+    function get_config_bounds(uint8 parameter) external view returns (uint256, uint256);
+    function get_config() external view returns (uint256, uint256, uint256, uint256, uint256, uint256);
+    function register_verifying_key(uint256[] calldata vk) external returns (bytes32);
+    function get_verifying_key(bytes32 vk_id) external view returns (uint256[] memory);
+    function verify_groth16(bytes32 vk_id, uint256[] calldata proof, uint256[] calldata public_inputs) external view returns (bool);
+    function verify_fri(uint8 log_degree, uint8 log_blowup, uint8 num_queries, bytes32[] calldata roots, uint64 final_value, uint64[] calldata values, bytes32[] calldata paths) external view returns (bool);
+    function benchmark_groth16(bytes32 vk_id, uint256[] calldata proof, uint256[] calldata public_inputs) external view returns (bool, uint256);
+    function price_option(uint256 spot, uint256 strike, uint256 rate, uint256 vol, uint256 time, bool is_call) external view returns (uint256);
+    function option_greeks(uint256 spot, uint256 strike, uint256 rate, uint256 vol, uint256 time, bool is_call) external view returns (int128, int128, int128, int128, int128);
+    function implied_volatility(uint256 price, uint256 spot, uint256 strike, uint256 rate, uint256 time, bool is_call) external view returns (uint256);
+    function optimize_yield(uint256 deposit, uint256[] calldata apys, uint256[] calldata risks, uint256[] calldata capacities, uint256 risk_budget) external view returns (uint256[] memory, uint256);
+    function price_asian_option(uint256 spot, uint256 strike, uint256 rate, uint256 vol, uint256 time, bool is_call, uint32 paths, uint32 steps, bytes32 seed) external view returns (uint256, uint256);
+    function price_barrier_option(uint256 spot, uint256 strike, uint256 barrier, uint8 barrier_type, uint256 rate, uint256 vol, uint256 time, bool is_call, uint32 paths, uint32 steps, bytes32 seed) external view returns (uint256, uint256);
+    function get_pool_volatility(bytes32 pool_id) external view returns (uint256, uint256);
+    function get_pool_observations(bytes32 pool_id) external view returns (uint256[] memory, uint256[] memory);
+    function set_pool_manager(address pool_manager) external;
+    function get_pool_manager() external view returns (address);
+    function hook_before_swap(bytes32 pool_id, address sender, uint256 amount_in, address token_in) external returns (uint256);
+    function hook_after_swap(bytes32 pool_id, uint256 price) external;
     function benchmark_step_verification(bytes32 step_verification_id) external returns (uint256);
     function benchmark_comparison() external returns (string[] memory, uint256[] memory);
     function benchmark_comprehensive() external returns (string[] memory, uint256[] memory);
@@ -23,9 +118,18 @@ contract ArbiProofIntegration {
     }
     
     // Example of how Solidity contracts can integrate with the Stylus implementation
-    function createDispute(bytes32 txHash) external payable returns (bytes32) {
+    function createDispute(
+        bytes32 assertionId,
+        uint256 traceLength,
+        bytes32 endStateHash,
+        bytes32 traceRoot,
+        bytes32[] calldata startProof,
+        bytes32[] calldata endProof
+    ) external payable returns (bytes32) {
         // Forward call to the Stylus contract for efficient processing
-        bytes32 disputeId = stylusImplementation.initiate_dispute{value: msg.value}(txHash, address(this));
+        bytes32 disputeId = stylusImplementation.initiate_dispute{value: msg.value}(
+            assertionId, traceLength, endStateHash, traceRoot, startProof, endProof
+        );
         
         emit DisputeCreated(disputeId, msg.sender);
         return disputeId;
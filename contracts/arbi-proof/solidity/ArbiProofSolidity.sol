@@ -30,12 +30,15 @@ contract ArbiProofSolidity {
     event BisectionChallenge(
         bytes32 indexed disputeId, 
         uint256 round,
-        bytes32 bisectionPoint
+        uint256 bisectionPoint,
+        bytes32 claimHash
     );
     
     event DisputeResolved(
         bytes32 indexed disputeId, 
-        address winner
+        address indexed winner,
+        uint8 outcome,
+        uint8 reason
     );
     
     // Benchmark function - used for gas comparison with Rust implementation
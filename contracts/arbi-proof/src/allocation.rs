@@ -0,0 +1,152 @@
+// Yield allocation across strategies under a risk budget, in 18-decimal
+// fixed point.
+//
+// Each strategy has an APY, a risk score and a capacity. The allocation
+// maximizes the expected yield sum(a_i * apy_i) subject to
+//
+//   sum(a_i) <= deposit,  sum(a_i * risk_i) <= risk_budget * deposit,
+//   0 <= a_i <= capacity_i
+//
+// so the risk budget caps the average risk of the whole deposit, with any
+// undeployed remainder counting as riskless. This is a linear program with
+// two coupling constraints, solved through its Lagrangian: for a price
+// lambda on risk, the best allocation just fills strategies in order of
+// apy - lambda * risk. Bisection finds the lambda at which that greedy
+// allocation crosses the budget, and the optimum is the mix of the
+// allocations just below and just above it that spends the budget exactly.
+
+use alloc::vec::Vec;
+use stylus_sdk::alloy_primitives::U256;
+
+use crate::fixed::{self, bounded, WAD};
+
+pub const MAX_STRATEGIES: usize = 32;
+pub const MAX_AMOUNT: i128 = 1_000_000_000_000 * WAD;
+pub const MAX_APY: i128 = 10 * WAD; // 1000%
+pub const MAX_RISK: i128 = 1_000 * WAD;
+
+const MAX_BISECTION_STEPS: usize = 128;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Strategy {
+    pub apy: i128,
+    pub risk: i128,
+    pub capacity: i128,
+}
+
+impl Strategy {
+    pub fn new(apy: U256, risk: U256, capacity: U256) -> Result<Self, &'static str> {
+        Ok(Self {
+            apy: bounded(apy, MAX_APY, true, "APY out of range")?,
+            risk: bounded(risk, MAX_RISK, true, "Risk score out of range")?,
+            capacity: bounded(capacity, MAX_AMOUNT, true, "Capacity out of range")?,
+        })
+    }
+}
+
+pub fn parse_amount(value: U256) -> Result<i128, &'static str> {
+    bounded(value, MAX_AMOUNT, true, "Amount out of range")
+}
+
+pub fn parse_risk(value: U256) -> Result<i128, &'static str> {
+    bounded(value, MAX_RISK, true, "Risk score out of range")
+}
+
+// Fills strategies with a positive risk-adjusted yield, best first; ties go
+// to the safer strategy when `prefer_safe`, else to the riskier one
+fn greedy(strategies: &[Strategy], deposit: i128, lambda: i128, prefer_safe: bool) -> Vec<i128> {
+    let score = |strategy: &Strategy| strategy.apy - fixed::mul(lambda, strategy.risk);
+    let mut order: Vec<usize> = (0..strategies.len()).collect();
+    order.sort_by(|&i, &j| {
+        let (a, b) = (&strategies[i], &strategies[j]);
+        let by_risk = if prefer_safe { a.risk.cmp(&b.risk) } else { b.risk.cmp(&a.risk) };
+        score(b).cmp(&score(a)).then(by_risk)
+    });
+
+    let mut allocation = alloc::vec![0; strategies.len()];
+    let mut remaining = deposit;
+    for index in order {
+        let strategy = &strategies[index];
+        if remaining == 0 || score(strategy) <= 0 {
+            break;
+        }
+        let amount = strategy.capacity.min(remaining);
+        allocation[index] = amount;
+        remaining -= amount;
+    }
+    allocation
+}
+
+fn risk_used(strategies: &[Strategy], allocation: &[i128]) -> i128 {
+    strategies
+        .iter()
+        .zip(allocation)
+        .map(|(strategy, amount)| fixed::mul(*amount, strategy.risk))
+        .sum()
+}
+
+// Amount per strategy, in input order
+pub fn optimize(strategies: &[Strategy], deposit: i128, risk_budget: i128) -> Vec<i128> {
+    let budget = fixed::mul(deposit, risk_budget);
+
+    let unconstrained = greedy(strategies, deposit, 0, true);
+    if risk_used(strategies, &unconstrained) <= budget {
+        return unconstrained;
+    }
+
+    // Past the highest apy / risk every risky strategy scores at most zero,
+    // so the greedy allocation only holds riskless ones
+    let mut low = 0;
+    let mut high = strategies
+        .iter()
+        .filter(|strategy| strategy.risk > 0)
+        .map(|strategy| fixed::div(strategy.apy, strategy.risk))
+        .max()
+        .unwrap_or(0)
+        .saturating_add(1);
+
+    for _ in 0..MAX_BISECTION_STEPS {
+        if high - low <= 1 {
+            break;
+        }
+        let mid = low + (high - low) / 2;
+        if risk_used(strategies, &greedy(strategies, deposit, mid, true)) > budget {
+            low = mid;
+        } else {
+            high = mid;
+        }
+    }
+
+    // Over budget at `low`, within it at `high`: mix the two so the risk
+    // lands on the budget
+    let over = greedy(strategies, deposit, low, false);
+    let within = greedy(strategies, deposit, high, true);
+    let (risk_over, risk_within) = (risk_used(strategies, &over), risk_used(strategies, &within));
+    let weight = fixed::div(budget - risk_within, risk_over - risk_within);
+
+    within
+        .iter()
+        .zip(&over)
+        .map(|(safe, risky)| {
+            let shift = risky - safe;
+            // Round each amount the way that lowers risk, so neither the
+            // budget nor the deposit is exceeded
+            let step = fixed::mul(weight, shift.abs());
+            if shift >= 0 {
+                safe + step
+            } else {
+                safe - (shift.abs() - fixed::mul(WAD - weight, shift.abs()))
+            }
+        })
+        .collect()
+}
+
+// Expected APY of the whole deposit, idle funds included
+pub fn blended_apy(strategies: &[Strategy], allocation: &[i128], deposit: i128) -> i128 {
+    let earned: i128 = strategies
+        .iter()
+        .zip(allocation)
+        .map(|(strategy, amount)| fixed::mul(*amount, strategy.apy))
+        .sum();
+    fixed::div(earned, deposit)
+}
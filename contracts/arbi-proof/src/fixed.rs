@@ -0,0 +1,203 @@
+// Signed 18-decimal fixed-point math, with no floats.
+//
+// Values are i128 scaled by 1e18 (WAD), which covers magnitudes up to about
+// 1.7e20. Products and quotients are taken in U256 and truncate toward zero,
+// saturating if the result leaves the i128 range, so callers bound their
+// inputs instead of checking every step.
+
+use stylus_sdk::alloy_primitives::U256;
+
+pub const WAD: i128 = 1_000_000_000_000_000_000;
+
+// ln 2 to 36 decimals, so k * ln 2 stays exact to 18 decimals for any k
+const LN_2_36: u128 = 693_147_180_559_945_309_417_232_121_458_176_568;
+const LN_2: i128 = 693_147_180_559_945_309;
+
+// exp saturates past e^46 (about 9.5e19) and is zero below e^-42
+pub const MAX_EXP_INPUT: i128 = 46 * WAD;
+const MIN_EXP_INPUT: i128 = -42 * WAD;
+
+// sqrt(2 * pi)
+pub const SQRT_2_PI: i128 = 2_506_628_274_631_000_502;
+
+// Reads an unsigned input as a fixed-point value, rejecting anything above
+// `max`, and zero unless `allow_zero`
+pub fn bounded(value: U256, max: i128, allow_zero: bool, error: &'static str) -> Result<i128, &'static str> {
+    if (value.is_zero() && !allow_zero) || value > U256::from(max as u128) {
+        return Err(error);
+    }
+    Ok(value.to::<u128>() as i128)
+}
+
+fn to_signed(magnitude: U256, negative: bool) -> i128 {
+    let magnitude = if magnitude > U256::from(i128::MAX as u128) {
+        i128::MAX
+    } else {
+        magnitude.to::<u128>() as i128
+    };
+    if negative {
+        -magnitude
+    } else {
+        magnitude
+    }
+}
+
+pub fn mul(a: i128, b: i128) -> i128 {
+    let product = U256::from(a.unsigned_abs()) * U256::from(b.unsigned_abs()) / U256::from(WAD as u128);
+    to_signed(product, (a < 0) != (b < 0))
+}
+
+// a / b; b must be non-zero
+pub fn div(a: i128, b: i128) -> i128 {
+    let quotient = U256::from(a.unsigned_abs()) * U256::from(WAD as u128) / U256::from(b.unsigned_abs());
+    to_signed(quotient, (a < 0) != (b < 0))
+}
+
+// k * ln 2 without losing the 18th decimal
+fn ln_2_times(k: i128) -> i128 {
+    let magnitude = U256::from(k.unsigned_abs()) * U256::from(LN_2_36) / U256::from(WAD as u128);
+    to_signed(magnitude, k < 0)
+}
+
+pub fn ln(x: i128) -> Result<i128, &'static str> {
+    if x <= 0 {
+        return Err("Logarithm of non-positive value");
+    }
+
+    // x = m * 2^k with m in [1, 2)
+    let raw = x as u128;
+    let wad = WAD as u128;
+    let mut k: i128 = 0;
+    let m = if raw >= wad {
+        while raw >= wad << (k + 1) {
+            k += 1;
+        }
+        (raw >> k) as i128
+    } else {
+        while raw << -k < wad {
+            k -= 1;
+        }
+        (raw << -k) as i128
+    };
+
+    // ln m = 2 atanh(z) = 2 (z + z^3/3 + z^5/5 + ...), z = (m - 1) / (m + 1) <= 1/3
+    let z = div(m - WAD, m + WAD);
+    let z_squared = mul(z, z);
+    let mut term = z;
+    let mut sum = 0;
+    let mut n = 1;
+    while term != 0 {
+        sum += term / n;
+        term = mul(term, z_squared);
+        n += 2;
+    }
+
+    Ok(ln_2_times(k) + 2 * sum)
+}
+
+pub fn exp(x: i128) -> Result<i128, &'static str> {
+    if x > MAX_EXP_INPUT {
+        return Err("Exponent too large");
+    }
+    if x < MIN_EXP_INPUT {
+        return Ok(0);
+    }
+
+    // e^x = e^r * 2^k with k = round(x / ln 2) and |r| <= ln 2 / 2
+    let k = (x + x.signum() * LN_2 / 2) / LN_2;
+    let r = x - ln_2_times(k);
+
+    let mut term = WAD;
+    let mut sum = WAD;
+    let mut n = 1;
+    while term != 0 {
+        term = mul(term, r) / n;
+        sum += term;
+        n += 1;
+    }
+
+    Ok(if k >= 0 { sum << k } else { sum >> -k })
+}
+
+fn isqrt(n: U256) -> U256 {
+    if n.is_zero() {
+        return n;
+    }
+    // Newton's method from a power of two above the root
+    let mut x = U256::from(1) << n.bit_len().div_ceil(2);
+    loop {
+        let y = (x + n / x) >> 1;
+        if y >= x {
+            return x;
+        }
+        x = y;
+    }
+}
+
+pub fn sqrt(x: i128) -> Result<i128, &'static str> {
+    if x < 0 {
+        return Err("Square root of negative value");
+    }
+    Ok(to_signed(isqrt(U256::from(x as u128) * U256::from(WAD as u128)), false))
+}
+
+// Standard normal CDF by Hart's algorithm 5666 (West, "Better approximations
+// to cumulative normal functions", 2005): absolute error below 1e-14
+pub fn norm_cdf(x: i128) -> i128 {
+    let x_abs = x.abs();
+
+    // Both tails are below 1e-18 past 9 standard deviations
+    let tail = if x_abs >= 9 * WAD {
+        0
+    } else {
+        let exponential = exp(-mul(x_abs, x_abs) / 2).unwrap_or(0);
+        if x_abs < 7_071_067_811_865_470_000 {
+            let horner = |coefficients: &[i128]| {
+                coefficients[1..]
+                    .iter()
+                    .fold(coefficients[0], |acc, c| mul(acc, x_abs) + c)
+            };
+            let numerator = horner(&[
+                35_262_496_599_891_100,
+                700_383_064_443_688_000,
+                6_373_962_203_531_650_000,
+                33_912_866_078_383_000_000,
+                112_079_291_497_871_000_000,
+                221_213_596_169_931_000_000,
+                220_206_867_912_376_000_000,
+            ]);
+            let denominator = horner(&[
+                88_388_347_648_318_400,
+                1_755_667_163_182_640_000,
+                16_064_177_579_207_000_000,
+                86_780_732_202_946_100_000,
+                296_564_248_779_674_000_000,
+                637_333_633_378_831_000_000,
+                793_826_512_519_948_000_000,
+                440_413_735_824_752_000_000,
+            ]);
+            div(mul(exponential, numerator), denominator)
+        } else {
+            // Continued fraction for the far tail
+            let mut build = x_abs + 650_000_000_000_000_000;
+            for c in [4, 3, 2, 1] {
+                build = x_abs + div(c * WAD, build);
+            }
+            div(div(exponential, build), SQRT_2_PI)
+        }
+    };
+
+    if x > 0 {
+        WAD - tail
+    } else {
+        tail
+    }
+}
+
+// Standard normal density, zero past 10 standard deviations
+pub fn norm_pdf(x: i128) -> i128 {
+    if x.abs() >= 10 * WAD {
+        return 0;
+    }
+    div(exp(-mul(x, x) / 2).unwrap_or(0), SQRT_2_PI)
+}
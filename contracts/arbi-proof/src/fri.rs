@@ -0,0 +1,323 @@
+// FRI low-degree test over the Goldilocks field (p = 2^64 - 2^32 + 1).
+//
+// The prover commits to the evaluations of f on the subgroup of size
+// n = degree * blowup, then repeatedly folds the function in half with a
+// Fiat-Shamir challenge beta:
+//
+//   f'(x^2) = (f(x) + f(-x)) / 2 + beta * (f(x) - f(-x)) / (2x)
+//
+// Each fold halves the degree bound, so after log2(degree) folds an honest
+// prover is left with a constant, which it sends in the clear. The verifier
+// samples query positions and checks that every fold along each query path
+// is consistent with the committed layers and ends at that constant. Each
+// query catches a function far from low degree with probability about
+// 1 - 1/blowup, so soundness is roughly queries * log2(blowup) bits.
+//
+// Layer l is committed as a Merkle tree whose leaf j pairs the evaluations
+// at w^j and -w^j = w^(j + n_l/2), hashed with the sorted-pair scheme of
+// merkle.rs; the position is bound into the leaf as
+//
+//   leaf = keccak256(uint64 j || uint64 f(w^j) || uint64 f(-w^j))
+//
+// The transcript is a keccak256 hash chain over the parameters, the layer
+// roots and the final value.
+
+use alloc::vec::Vec;
+use stylus_sdk::alloy_primitives::FixedBytes;
+use sha3::{Digest, Keccak256};
+
+use crate::merkle;
+
+pub const MODULUS: u64 = 0xffff_ffff_0000_0001;
+// Generator of the multiplicative group, whose order is divisible by 2^32
+const GENERATOR: u64 = 7;
+// 1/2 = (p + 1) / 2
+const INV_TWO: u64 = 0x7fff_ffff_8000_0001;
+
+pub const MAX_LOG_DEGREE: u32 = 16;
+pub const MAX_LOG_BLOWUP: u32 = 4;
+pub const MAX_QUERIES: usize = 64;
+
+pub fn add(a: u64, b: u64) -> u64 {
+    ((a as u128 + b as u128) % MODULUS as u128) as u64
+}
+
+pub fn sub(a: u64, b: u64) -> u64 {
+    ((a as u128 + MODULUS as u128 - b as u128) % MODULUS as u128) as u64
+}
+
+pub fn mul(a: u64, b: u64) -> u64 {
+    ((a as u128 * b as u128) % MODULUS as u128) as u64
+}
+
+pub fn pow(mut base: u64, mut exponent: u64) -> u64 {
+    let mut result = 1;
+    while exponent > 0 {
+        if exponent & 1 == 1 {
+            result = mul(result, base);
+        }
+        base = mul(base, base);
+        exponent >>= 1;
+    }
+    result
+}
+
+pub fn inverse(a: u64) -> u64 {
+    pow(a, MODULUS - 2)
+}
+
+// Generator of the subgroup of order 2^log_size
+pub fn root_of_unity(log_size: u32) -> u64 {
+    pow(GENERATOR, (MODULUS - 1) >> log_size)
+}
+
+// Value at x^2 of the folded function, from f(x) and f(-x)
+fn fold(a: u64, b: u64, beta: u64, x: u64) -> u64 {
+    let even = mul(add(a, b), INV_TWO);
+    let odd = mul(mul(sub(a, b), INV_TWO), inverse(x));
+    add(even, mul(beta, odd))
+}
+
+fn leaf(index: u64, a: u64, b: u64) -> FixedBytes<32> {
+    let mut hasher = Keccak256::new();
+    hasher.update(index.to_be_bytes());
+    hasher.update(a.to_be_bytes());
+    hasher.update(b.to_be_bytes());
+    FixedBytes::from_slice(&hasher.finalize())
+}
+
+struct Transcript {
+    state: FixedBytes<32>,
+}
+
+impl Transcript {
+    fn new(log_degree: u32, log_blowup: u32, queries: usize) -> Self {
+        let mut transcript = Self { state: FixedBytes::default() };
+        transcript.absorb(&log_degree.to_be_bytes());
+        transcript.absorb(&log_blowup.to_be_bytes());
+        transcript.absorb(&(queries as u32).to_be_bytes());
+        transcript
+    }
+
+    fn absorb(&mut self, data: &[u8]) {
+        let mut hasher = Keccak256::new();
+        hasher.update(self.state.as_slice());
+        hasher.update(data);
+        self.state = FixedBytes::from_slice(&hasher.finalize());
+    }
+
+    fn squeeze(&mut self) -> u64 {
+        self.absorb(&[]);
+        let mut word = [0u8; 8];
+        word.copy_from_slice(&self.state[..8]);
+        u64::from_be_bytes(word)
+    }
+
+    fn challenge(&mut self) -> u64 {
+        self.squeeze() % MODULUS
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FriProof {
+    // Root of every layer before the final constant
+    pub roots: Vec<FixedBytes<32>>,
+    pub final_value: u64,
+    // For each query, then each layer: f(w^j) and f(-w^j)
+    pub values: Vec<u64>,
+    // For each query, then each layer: the leaf's Merkle siblings
+    pub paths: Vec<FixedBytes<32>>,
+}
+
+fn check_params(log_degree: u32, log_blowup: u32, queries: usize) -> Result<(), &'static str> {
+    if log_degree == 0 || log_degree > MAX_LOG_DEGREE {
+        return Err("Unsupported degree bound");
+    }
+    if log_blowup == 0 || log_blowup > MAX_LOG_BLOWUP {
+        return Err("Unsupported blowup factor");
+    }
+    if queries == 0 || queries > MAX_QUERIES {
+        return Err("Unsupported query count");
+    }
+    Ok(())
+}
+
+// Checks that the committed function is close to a polynomial of degree
+// below 2^log_degree. Malformed proofs are errors; proofs that fail a check
+// verify as false.
+pub fn verify(log_degree: u32, log_blowup: u32, queries: usize, proof: &FriProof) -> Result<bool, &'static str> {
+    check_params(log_degree, log_blowup, queries)?;
+    let log_size = log_degree + log_blowup;
+    let layers = log_degree as usize;
+
+    // Layer l has 2^(log_size - l - 1) leaves
+    let path_len: usize = (0..log_degree).map(|layer| (log_size - layer - 1) as usize).sum();
+    if proof.roots.len() != layers
+        || proof.values.len() != queries * layers * 2
+        || proof.paths.len() != queries * path_len
+    {
+        return Err("Malformed FRI proof");
+    }
+    if proof.final_value >= MODULUS || proof.values.iter().any(|value| *value >= MODULUS) {
+        return Err("FRI value out of range");
+    }
+
+    let mut transcript = Transcript::new(log_degree, log_blowup, queries);
+    let betas: Vec<u64> = proof
+        .roots
+        .iter()
+        .map(|root| {
+            transcript.absorb(root.as_slice());
+            transcript.challenge()
+        })
+        .collect();
+    transcript.absorb(&proof.final_value.to_be_bytes());
+
+    let mut values = proof.values.chunks(2);
+    let mut paths = proof.paths.as_slice();
+    for _ in 0..queries {
+        let mut half = 1u64 << (log_size - 1);
+        let mut index = transcript.squeeze() % half;
+        let mut folded = None;
+
+        for (layer, (root, beta)) in proof.roots.iter().zip(&betas).enumerate() {
+            let pair = values.next().ok_or("Malformed FRI proof")?;
+            let (a, b) = (pair[0], pair[1]);
+
+            // The previous fold landed on one of this leaf's two points
+            if let Some(expected) = folded {
+                let opened = if index < half { a } else { b };
+                if opened != expected {
+                    return Ok(false);
+                }
+                index %= half;
+            }
+
+            let depth = (log_size - layer as u32 - 1) as usize;
+            let (path, rest) = paths.split_at(depth);
+            paths = rest;
+            if !merkle::verify(path, *root, leaf(index, a, b)) {
+                return Ok(false);
+            }
+
+            let x = pow(root_of_unity(log_size - layer as u32), index);
+            folded = Some(fold(a, b, *beta, x));
+            half >>= 1;
+        }
+
+        // The folded value sits at `index` of a layer of 2 * half points,
+        // all equal to the final constant
+        if folded != Some(proof.final_value) {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+// Evaluations of the polynomial with `coefficients` on the subgroup of
+// order 2^log_size
+pub fn evaluate(coefficients: &[u64], log_size: u32) -> Vec<u64> {
+    let omega = root_of_unity(log_size);
+    let mut x = 1;
+    (0..1u64 << log_size)
+        .map(|_| {
+            let value = coefficients.iter().rev().fold(0, |acc, c| add(mul(acc, x), *c));
+            x = mul(x, omega);
+            value
+        })
+        .collect()
+}
+
+// Merkle tree over the leaves of one layer, from the leaves up
+fn commit(evaluations: &[u64]) -> Vec<Vec<FixedBytes<32>>> {
+    let half = evaluations.len() / 2;
+    let mut levels = Vec::new();
+    levels.push(
+        (0..half)
+            .map(|j| leaf(j as u64, evaluations[j], evaluations[j + half]))
+            .collect::<Vec<_>>(),
+    );
+    while levels.last().unwrap().len() > 1 {
+        let next = levels
+            .last()
+            .unwrap()
+            .chunks(2)
+            .map(|pair| merkle::hash_pair(pair[0], pair[1]))
+            .collect();
+        levels.push(next);
+    }
+    levels
+}
+
+// Proves that `evaluations`, over the subgroup of their size, come from a
+// polynomial of degree below 2^log_degree. Used to build the benchmark
+// instance and test vectors; nothing on chain relies on it.
+pub fn prove(evaluations: &[u64], log_degree: u32, queries: usize) -> Result<FriProof, &'static str> {
+    if !evaluations.len().is_power_of_two() {
+        return Err("Domain size must be a power of two");
+    }
+    let log_size = evaluations.len().trailing_zeros();
+    let log_blowup = log_size.checked_sub(log_degree).ok_or("Domain smaller than degree bound")?;
+    check_params(log_degree, log_blowup, queries)?;
+
+    let mut transcript = Transcript::new(log_degree, log_blowup, queries);
+    let mut layer = evaluations.to_vec();
+    let mut trees = Vec::new();
+    let mut layers = Vec::new();
+    for round in 0..log_degree {
+        let tree = commit(&layer);
+        let root = tree.last().unwrap()[0];
+        transcript.absorb(root.as_slice());
+        let beta = transcript.challenge();
+
+        let half = layer.len() / 2;
+        let omega = root_of_unity(log_size - round);
+        let mut x = 1;
+        let next = (0..half)
+            .map(|j| {
+                let value = fold(layer[j], layer[j + half], beta, x);
+                x = mul(x, omega);
+                value
+            })
+            .collect();
+        trees.push(tree);
+        layers.push(core::mem::replace(&mut layer, next));
+    }
+
+    let final_value = layer[0];
+    transcript.absorb(&final_value.to_be_bytes());
+
+    let mut values = Vec::new();
+    let mut paths = Vec::new();
+    for _ in 0..queries {
+        let mut index = (transcript.squeeze() % (1u64 << (log_size - 1))) as usize;
+        for (evaluations, tree) in layers.iter().zip(&trees) {
+            let half = evaluations.len() / 2;
+            index %= half;
+            values.push(evaluations[index]);
+            values.push(evaluations[index + half]);
+
+            let mut position = index;
+            for level in &tree[..tree.len() - 1] {
+                paths.push(level[position ^ 1]);
+                position >>= 1;
+            }
+        }
+    }
+
+    Ok(FriProof {
+        roots: trees.iter().map(|tree| tree.last().unwrap()[0]).collect(),
+        final_value,
+        values,
+        paths,
+    })
+}
+
+// Degree < 16 polynomial at blowup 8 with 16 queries, for the gas benchmark
+pub fn benchmark_instance() -> (u32, u32, usize, FriProof) {
+    let (log_degree, log_blowup, queries) = (4, 3, 16);
+    let coefficients: Vec<u64> = (1..=16).collect();
+    let evaluations = evaluate(&coefficients, log_degree + log_blowup);
+    let proof = prove(&evaluations, log_degree, queries).expect("valid benchmark parameters");
+    (log_degree, log_blowup, queries, proof)
+}
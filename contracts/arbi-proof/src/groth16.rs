@@ -0,0 +1,181 @@
+// Groth16 verification over BN254 (alt_bn128) through the EVM precompiles.
+//
+// Points are 256-bit words in the precompiles' encoding: a G1 point is
+// (x, y) and a G2 point is (x.c1, x.c0, y.c1, y.c0), imaginary part first,
+// which is also how snarkjs orders them in its Solidity verifiers. A
+// verifying key is laid out as
+//
+//   alpha (G1) | beta (G2) | gamma (G2) | delta (G2) | IC_0 .. IC_n (G1)
+//
+// for n public inputs, and a proof as A (G1) | B (G2) | C (G1). The proof is
+// accepted when
+//
+//   e(-A, B) * e(alpha, beta) * e(vk_x, gamma) * e(C, delta) == 1
+//
+// with vk_x = IC_0 + sum(input_i * IC_{i+1}).
+
+use alloc::vec::Vec;
+use stylus_sdk::{
+    alloy_primitives::{Address, U256},
+    call::{self, StaticCallContext},
+};
+
+const EC_ADD: Address = Address::with_last_byte(6);
+const EC_MUL: Address = Address::with_last_byte(7);
+const EC_PAIRING: Address = Address::with_last_byte(8);
+
+// Base field modulus p
+pub const FIELD_MODULUS: U256 = U256::from_limbs([
+    0x3c208c16d87cfd47,
+    0x97816a916871ca8d,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+]);
+
+// Scalar field modulus r; public inputs must be reduced below it
+pub const SCALAR_MODULUS: U256 = U256::from_limbs([
+    0x43e1f593f0000001,
+    0x2833e84879b97091,
+    0xb85045b68181585d,
+    0x30644e72e131a029,
+]);
+
+// alpha, beta, gamma and delta, ahead of the IC points
+pub const VK_FIXED_WORDS: usize = 14;
+pub const PROOF_WORDS: usize = 8;
+
+// Number of public inputs a verifying key of `len` words takes, if the
+// layout is valid
+pub fn input_count(len: usize) -> Option<usize> {
+    if len < VK_FIXED_WORDS + 2 || !(len - VK_FIXED_WORDS).is_multiple_of(2) {
+        return None;
+    }
+    Some((len - VK_FIXED_WORDS) / 2 - 1)
+}
+
+fn encode(words: &[U256]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_be_bytes::<32>()).collect()
+}
+
+fn decode_point(output: &[u8]) -> Result<[U256; 2], &'static str> {
+    if output.len() != 64 {
+        return Err("Invalid curve point");
+    }
+    Ok([U256::from_be_slice(&output[..32]), U256::from_be_slice(&output[32..])])
+}
+
+fn ec_add(context: impl StaticCallContext, p: [U256; 2], q: [U256; 2]) -> Result<[U256; 2], &'static str> {
+    let output = call::static_call(context, EC_ADD, &encode(&[p[0], p[1], q[0], q[1]]))
+        .map_err(|_| "Invalid curve point")?;
+    decode_point(&output)
+}
+
+fn ec_mul(context: impl StaticCallContext, p: [U256; 2], scalar: U256) -> Result<[U256; 2], &'static str> {
+    let output = call::static_call(context, EC_MUL, &encode(&[p[0], p[1], scalar]))
+        .map_err(|_| "Invalid curve point")?;
+    decode_point(&output)
+}
+
+// Additive inverse of a G1 point with coordinates below p; the point at
+// infinity is its own inverse
+pub fn negate(p: [U256; 2]) -> [U256; 2] {
+    if p[1].is_zero() {
+        return p;
+    }
+    [p[0], FIELD_MODULUS - p[1]]
+}
+
+pub fn verify<C: StaticCallContext + Copy>(
+    context: C,
+    vk: &[U256],
+    proof: &[U256],
+    inputs: &[U256],
+) -> Result<bool, &'static str> {
+    let count = input_count(vk.len()).ok_or("Malformed verifying key")?;
+    if proof.len() != PROOF_WORDS {
+        return Err("Malformed proof");
+    }
+    if inputs.len() != count {
+        return Err("Wrong number of public inputs");
+    }
+    if proof.iter().any(|word| *word >= FIELD_MODULUS) {
+        return Err("Proof coordinate out of range");
+    }
+
+    let mut vk_x = [vk[VK_FIXED_WORDS], vk[VK_FIXED_WORDS + 1]];
+    for (i, input) in inputs.iter().enumerate() {
+        if *input >= SCALAR_MODULUS {
+            return Err("Public input out of range");
+        }
+        let ic = VK_FIXED_WORDS + 2 * (i + 1);
+        let term = ec_mul(context, [vk[ic], vk[ic + 1]], *input)?;
+        vk_x = ec_add(context, vk_x, term)?;
+    }
+
+    let neg_a = negate([proof[0], proof[1]]);
+    let mut pairing = Vec::with_capacity(24);
+    pairing.extend_from_slice(&neg_a);
+    pairing.extend_from_slice(&proof[2..6]);
+    pairing.extend_from_slice(&vk[..6]); // alpha, beta
+    pairing.extend_from_slice(&vk_x);
+    pairing.extend_from_slice(&vk[6..10]); // gamma
+    pairing.extend_from_slice(&proof[6..8]);
+    pairing.extend_from_slice(&vk[10..14]); // delta
+
+    // The precompile rejects points off the curve or outside the subgroup
+    let output = call::static_call(context, EC_PAIRING, &encode(&pairing))
+        .map_err(|_| "Invalid pairing input")?;
+    if output.len() != 32 {
+        return Err("Invalid pairing input");
+    }
+    Ok(U256::from_be_slice(&output) == U256::from(1))
+}
+
+// Synthetic instance for the gas benchmark, built from the generators with
+// known discrete logs rather than a trusted setup: every G2 point is the G2
+// generator h, alpha = 2g, IC_0 = 3g, IC_1 = 5g, C = 11g and A = 51g for the
+// public input 7, so that 51 = 2 + (3 + 7 * 5) + 11. It exercises the same
+// precompile calls as a real one-input circuit.
+const G2_GENERATOR: [U256; 4] = [
+    U256::from_limbs([0x97e485b7aef312c2, 0xf1aa493335a9e712, 0x7260bfb731fb5d25, 0x198e9393920d483a]),
+    U256::from_limbs([0x46debd5cd992f6ed, 0x674322d4f75edadd, 0x426a00665e5c4479, 0x1800deef121f1e76]),
+    U256::from_limbs([0x55acdadcd122975b, 0xbc4b313370b38ef3, 0xec9e99ad690c3395, 0x090689d0585ff075]),
+    U256::from_limbs([0x4ce6cc0166fa7daa, 0xe3d1e7690c43d37b, 0x4aab71808dcb408f, 0x12c85ea5db8c6deb]),
+];
+const G1_TIMES_2: [U256; 2] = [
+    U256::from_limbs([0xd3c208c16d87cfd3, 0xd97816a916871ca8, 0x9b85045b68181585, 0x030644e72e131a02]),
+    U256::from_limbs([0xff3ebf7a5a18a2c4, 0x68a6a449e3538fc7, 0xe7845f96b2ae9c0a, 0x15ed738c0e0a7c92]),
+];
+const G1_TIMES_3: [U256; 2] = [
+    U256::from_limbs([0xf2d355961915abf0, 0x9315d84715b8e679, 0xf40232bcb1b6bd15, 0x0769bf9ac56bea3f]),
+    U256::from_limbs([0xcdf1ff3dd9fe2261, 0x319e63b40b9c5b57, 0x554fdb7c8d086475, 0x2ab799bee0489429]),
+];
+const G1_TIMES_5: [U256; 2] = [
+    U256::from_limbs([0xe849a8a7fa163fa9, 0x62e4ded88953a39c, 0x66bc0204762b7743, 0x17c139df0efee0f7]),
+    U256::from_limbs([0x3ffc5718c6d4cc7c, 0x0baa9258e0b95927, 0x4764a357af8a9fe7, 0x01e0559bacb16066]),
+];
+const G1_TIMES_11: [U256; 2] = [
+    U256::from_limbs([0xa8b71f80fc3d49ef, 0x2482477fe92ac12c, 0x9da8808651eecdb8, 0x2a14705537b00918]),
+    U256::from_limbs([0xb6177e19ea67ec38, 0x7a618c779fd4717d, 0x8e1ddf1402925887, 0x2df7ee7f243ea8b3]),
+];
+const G1_TIMES_51: [U256; 2] = [
+    U256::from_limbs([0x82a4510da7ba666c, 0x5a3b0dc556a16011, 0x98f6a86d9e33a622, 0x20c0a5faf4f62eec]),
+    U256::from_limbs([0x066a2557d1294758, 0xad8f6f2755c148b1, 0xa153d9e5f5cfc29a, 0x09321ac7227a8c67]),
+];
+
+pub fn benchmark_instance() -> (Vec<U256>, Vec<U256>, Vec<U256>) {
+    let mut vk = Vec::with_capacity(VK_FIXED_WORDS + 4);
+    vk.extend_from_slice(&G1_TIMES_2);
+    for _ in 0..3 {
+        vk.extend_from_slice(&G2_GENERATOR);
+    }
+    vk.extend_from_slice(&G1_TIMES_3);
+    vk.extend_from_slice(&G1_TIMES_5);
+
+    let mut proof = Vec::with_capacity(PROOF_WORDS);
+    proof.extend_from_slice(&G1_TIMES_51);
+    proof.extend_from_slice(&G2_GENERATOR);
+    proof.extend_from_slice(&G1_TIMES_11);
+
+    (vk, proof, alloc::vec![U256::from(7)])
+}
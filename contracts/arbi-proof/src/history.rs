@@ -0,0 +1,181 @@
+// BoLD-style history commitments.
+//
+// A history commitment is the root of a Merkle tree over the state hashes of
+// an execution, together with its height (the number of leaves). Trees are
+// kept as Merkle expansions: entry i is the root of a complete subtree of
+// 2^i leaves, or zero when the size has no such subtree, so appending works
+// like binary addition. Leaves are keccak256(state_hash), smaller subtrees
+// are padded with zero on the right, and larger subtrees sit on the left,
+// matching Arbitrum BoLD's MerkleTreeLib.
+//
+// A prefix proof shows that a commitment of height n is a prefix of one of
+// height m: starting from the expansion of the first, it lists the complete
+// subtrees to append, largest aligned first, until the height reaches m.
+
+use alloc::vec;
+use alloc::vec::Vec;
+use stylus_sdk::alloy_primitives::FixedBytes;
+use sha3::{Digest, Keccak256};
+
+// Expansions cover trees of up to 2^64 - 1 leaves
+pub const MAX_LEVEL: usize = 64;
+
+fn hash_nodes(left: FixedBytes<32>, right: FixedBytes<32>) -> FixedBytes<32> {
+    let mut hasher = Keccak256::new();
+    hasher.update(left.as_slice());
+    hasher.update(right.as_slice());
+    FixedBytes::from_slice(&hasher.finalize())
+}
+
+fn most_significant_bit(x: u64) -> usize {
+    63 - x.leading_zeros() as usize
+}
+
+// Number of leaves the expansion commits to
+pub fn tree_size(expansion: &[FixedBytes<32>]) -> u64 {
+    expansion
+        .iter()
+        .enumerate()
+        .filter(|(_, node)| **node != FixedBytes::<32>::default())
+        .fold(0, |size, (level, _)| size | (1 << level))
+}
+
+pub fn root(expansion: &[FixedBytes<32>]) -> Result<FixedBytes<32>, &'static str> {
+    if expansion.is_empty() {
+        return Err("Empty merkle expansion");
+    }
+    if expansion.len() > MAX_LEVEL {
+        return Err("Merkle expansion too large");
+    }
+
+    let zero = FixedBytes::<32>::default();
+    let mut accum = zero;
+    for (level, node) in expansion.iter().enumerate() {
+        if accum == zero {
+            if *node != zero {
+                accum = *node;
+                // Only a lone top-level subtree is already balanced
+                if level != expansion.len() - 1 {
+                    accum = hash_nodes(accum, zero);
+                }
+            }
+        } else if *node != zero {
+            accum = hash_nodes(*node, accum);
+        } else {
+            accum = hash_nodes(accum, zero);
+        }
+    }
+    Ok(accum)
+}
+
+// Appends a complete subtree of 2^level leaves; the expansion must not have
+// any smaller subtree, or the new leaves would not be aligned
+pub fn append_complete_subtree(
+    expansion: &[FixedBytes<32>],
+    level: usize,
+    subtree_root: FixedBytes<32>,
+) -> Result<Vec<FixedBytes<32>>, &'static str> {
+    let zero = FixedBytes::<32>::default();
+    if level >= MAX_LEVEL {
+        return Err("Level too high");
+    }
+    if subtree_root == zero {
+        return Err("Cannot append empty subtree");
+    }
+
+    if expansion.is_empty() {
+        let mut next = vec![zero; level + 1];
+        next[level] = subtree_root;
+        return Ok(next);
+    }
+    if level >= expansion.len() {
+        return Err("Level greater than highest level of current expansion");
+    }
+
+    let post_size = tree_size(expansion)
+        .checked_add(1 << level)
+        .ok_or("Merkle expansion too large")?;
+    let mut next = vec![zero; most_significant_bit(post_size) + 1];
+
+    let mut accum = subtree_root;
+    for (i, node) in expansion.iter().enumerate() {
+        if i < level {
+            if *node != zero {
+                return Err("Append above least significant bit");
+            }
+        } else if accum == zero {
+            next[i] = *node;
+        } else if *node == zero {
+            next[i] = accum;
+            accum = zero;
+        } else {
+            accum = hash_nodes(*node, accum);
+        }
+    }
+    if accum != zero {
+        let top = next.len() - 1;
+        next[top] = accum;
+    }
+
+    Ok(next)
+}
+
+pub fn append_leaf(expansion: &[FixedBytes<32>], state_hash: FixedBytes<32>) -> Result<Vec<FixedBytes<32>>, &'static str> {
+    let leaf = FixedBytes::from_slice(&Keccak256::digest(state_hash.as_slice()));
+    append_complete_subtree(expansion, 0, leaf)
+}
+
+// Level of the largest complete subtree that can be appended to a tree of
+// `start` leaves without passing `end`
+fn maximum_append_between(start: u64, end: u64) -> usize {
+    let msb = most_significant_bit(start ^ end);
+    let mask = if msb == 63 { u64::MAX } else { (1 << (msb + 1)) - 1 };
+    let y = start & mask;
+    let z = end & mask;
+    if y != 0 {
+        y.trailing_zeros() as usize
+    } else {
+        most_significant_bit(z)
+    }
+}
+
+// Checks that (pre_root, pre_size) is a prefix of (post_root, post_size)
+pub fn verify_prefix_proof(
+    pre_root: FixedBytes<32>,
+    pre_size: u64,
+    post_root: FixedBytes<32>,
+    post_size: u64,
+    pre_expansion: &[FixedBytes<32>],
+    proof: &[FixedBytes<32>],
+) -> Result<(), &'static str> {
+    if pre_size == 0 {
+        return Err("Pre-size cannot be 0");
+    }
+    if pre_size >= post_size {
+        return Err("Pre-size must be less than post-size");
+    }
+    if tree_size(pre_expansion) != pre_size {
+        return Err("Pre expansion does not match pre-size");
+    }
+    if root(pre_expansion)? != pre_root {
+        return Err("Pre expansion root mismatch");
+    }
+
+    let mut expansion = pre_expansion.to_vec();
+    let mut size = pre_size;
+    let mut subtrees = proof.iter();
+    while size < post_size {
+        let level = maximum_append_between(size, post_size);
+        let subtree_root = subtrees.next().ok_or("Prefix proof too short")?;
+        expansion = append_complete_subtree(&expansion, level, *subtree_root)?;
+        size += 1 << level;
+    }
+
+    if subtrees.next().is_some() {
+        return Err("Prefix proof too long");
+    }
+    if root(&expansion)? != post_root {
+        return Err("Post expansion root mismatch");
+    }
+    Ok(())
+}
@@ -1,21 +1,40 @@
-#![cfg_attr(not(feature = "export-abi"), no_main)]
+#![cfg_attr(not(any(feature = "export-abi", test)), no_main)]
 extern crate alloc;
 
 use alloc::vec::Vec;
 use stylus_sdk::{
+    abi::Bytes,
     alloy_primitives::{Address, FixedBytes, U256},
-    block, evm, msg,
+    call, contract, evm,
     prelude::*,
     stylus_proc::entrypoint,
 };
+#[cfg(not(test))]
+use stylus_sdk::{block, msg};
+#[cfg(test)]
+use testing::{block, msg};
 use sha3::{Digest, Keccak256};
-use hex_literal::hex; // NEW import
+use alloy_sol_types::sol;
+
+mod allocation;
+mod fixed;
+mod fri;
+mod groth16;
+mod history;
+mod merkle;
+mod montecarlo;
+mod options;
+mod oracle;
+#[cfg(test)]
+mod testing;
+mod vm;
 
 #[global_allocator]
 static ALLOC: wee_alloc::WeeAlloc = wee_alloc::WeeAlloc::INIT;
 
 sol_storage! {
     #[entrypoint]
+    #[allow(non_snake_case)] // field names mirror the Solidity storage layout
     pub struct ArbiProofSimulator {
         // Dispute storage - store each field separately
         mapping(bytes32 => address) disputeChallenger;
@@ -24,40 +43,106 @@ sol_storage! {
         mapping(bytes32 => uint256) disputeCurrentRound;
         mapping(bytes32 => uint256) disputeTotalRounds;
         mapping(bytes32 => uint256) disputeTimestamp;
-        mapping(bytes32 => bytes32) disputeTxHash;
+        mapping(bytes32 => bytes32) disputeAssertionId;
+
+        // Disputed execution-trace steps [start, end], narrowed by each round
+        mapping(bytes32 => uint256) disputeRangeStart;
+        mapping(bytes32 => uint256) disputeRangeEnd;
+        // Machine state hashes: agreed at range start, claimed at range end
+        mapping(bytes32 => bytes32) disputeStartHash;
+        mapping(bytes32 => bytes32) disputeChallengerEndHash;
+        mapping(bytes32 => bytes32) disputeDefenderEndHash;
+        // Each party's Merkle root over the state hash after every trace step
+        mapping(bytes32 => bytes32) disputeTraceRoot;
+        mapping(bytes32 => bytes32) disputeDefenderTraceRoot;
+        // Protocol mode, see MODE_*; in history mode the start and end hashes
+        // above are history commitments instead of state hashes
+        mapping(bytes32 => uint256) disputeMode;
+
+        // Stake escrow - wei held by the contract for each open dispute
+        mapping(bytes32 => uint256) disputeChallengerStake;
+        mapping(bytes32 => uint256) disputeDefenderStake;
+        mapping(bytes32 => uint256) disputeBondDeadline;
+        mapping(bytes32 => uint256) disputeClockBudget; // each party's starting clock
+
+        // Chess clocks - seconds each party has left, charged only on their turn
+        mapping(bytes32 => uint256) disputeChallengerClock;
+        mapping(bytes32 => uint256) disputeDefenderClock;
+        mapping(bytes32 => uint256) disputeLastMoveAt;
+
+        // How a resolved dispute ended, see OUTCOME_* and REASON_*
+        mapping(bytes32 => uint256) disputeOutcome;
+        mapping(bytes32 => uint256) disputeReason;
+        mapping(bytes32 => address) disputeWinner;
+
+        // Protocol fee taken from the pot on settlement
+        address owner;
+        uint256 protocolFeeBps;
+        address feeRecipient;
+
+        // Assertion registry: proposers bond a state root on top of a parent
+        // assertion, and every dispute on it is fought against the proposer
+        mapping(bytes32 => address) assertionProposer;
+        mapping(bytes32 => bytes32) assertionParent;
+        mapping(bytes32 => bytes32) assertionStateRoot;
+        mapping(bytes32 => uint256) assertionBond;
+        mapping(bytes32 => uint256) assertionDeadline; // end of the challenge window
+        mapping(bytes32 => uint256) assertionStatus;
+        mapping(bytes32 => uint256) assertionOpenCount;
+        mapping(bytes32 => mapping(uint256 => bytes32)) assertionOpenDisputes;
+        mapping(bytes32 => uint256) disputeOpenIndex; // 1-based slot in the open list
+        // State root that assertions with a zero parent build on
+        bytes32 genesisStateRoot;
+
+        // Disputes opened by each challenger so far, mixed into dispute ids
+        mapping(address => uint256) disputeNonce;
+
+        // Enumerable dispute index with 0-based positions; a challenger's list
+        // is indexed by the nonce each dispute consumed
+        uint256 disputeCount;
+        mapping(uint256 => bytes32) disputeIds;
+        mapping(address => mapping(uint256 => bytes32)) challengerDisputes;
+        mapping(address => uint256) defenderDisputeCount;
+        mapping(address => mapping(uint256 => bytes32)) defenderDisputes;
+        mapping(uint256 => uint256) statusCount; // disputes per status code
+
+        // Owner-managed game parameters, keyed by CONFIG_* id (zero means default)
+        mapping(uint256 => uint256) config;
+        // Range a per-dispute override of each parameter has to fall in; while
+        // unset, only the configured value itself
+        mapping(uint256 => uint256) configMin;
+        mapping(uint256 => uint256) configMax;
+
+        // Registered Groth16 verifying keys, keyed by the hash of their words
+        mapping(bytes32 => uint256) vkLength;
+        mapping(bytes32 => mapping(uint256 => uint256)) vkWords;
         
-        // Challenge rounds storage - simplified approach
+        // Challenge rounds storage, keyed by dispute and 1-based round
         mapping(bytes32 => uint256) roundsCount;
-        // Need to use primitive mappings instead of nested mappings
-        mapping(bytes32 => uint256) roundStatus0;
-        mapping(bytes32 => uint256) roundStatus1;
-        mapping(bytes32 => uint256) roundStatus2;
-        mapping(bytes32 => uint256) roundStatus3;
-        mapping(bytes32 => uint256) roundStatus4;
-        mapping(bytes32 => uint256) roundBisectionPoint0;
-        mapping(bytes32 => uint256) roundBisectionPoint1;
-        mapping(bytes32 => uint256) roundBisectionPoint2;
-        mapping(bytes32 => uint256) roundBisectionPoint3;
-        mapping(bytes32 => uint256) roundBisectionPoint4;
-        mapping(bytes32 => bytes32) roundChallengerClaim0;
-        mapping(bytes32 => bytes32) roundChallengerClaim1;
-        mapping(bytes32 => bytes32) roundChallengerClaim2;
-        mapping(bytes32 => bytes32) roundChallengerClaim3;
-        mapping(bytes32 => bytes32) roundChallengerClaim4;
-        mapping(bytes32 => bytes32) roundDefenderResponse0;
-        mapping(bytes32 => bytes32) roundDefenderResponse1;
-        mapping(bytes32 => bytes32) roundDefenderResponse2;
-        mapping(bytes32 => bytes32) roundDefenderResponse3;
-        mapping(bytes32 => bytes32) roundDefenderResponse4;
+        mapping(bytes32 => mapping(uint256 => uint256)) roundStatus;
+        mapping(bytes32 => mapping(uint256 => uint256)) roundBisectionPoint;
+        mapping(bytes32 => mapping(uint256 => bytes32)) roundChallengerClaim;
+        mapping(bytes32 => mapping(uint256 => bytes32)) roundDefenderResponse;
         
         // Gas benchmarking counters
         mapping(bytes32 => uint256) benchmarkData;
         
         // Add fields for Uniswap v4 style hook
+        address poolManager; // only caller the swap hooks accept
         mapping(bytes32 => uint256) poolLiquidity;
         mapping(address => uint256) userBalances;
         mapping(bytes32 => uint256) swapFees;
+        // EWMA of annualized squared log returns, see oracle.rs
         mapping(bytes32 => uint256) poolVolatility;
+
+        // Price observations per pool, a ring buffer of OBSERVATION_CAPACITY
+        // slots indexed by the running count
+        mapping(bytes32 => uint256) poolObservationCount;
+        mapping(bytes32 => mapping(uint256 => uint256)) poolObservationPrice;
+        mapping(bytes32 => mapping(uint256 => uint256)) poolObservationTime;
+        // Variance before the latest second's return was folded in, which
+        // later swaps in that same second resample from
+        mapping(bytes32 => uint256) poolVarianceBefore;
     }
 }
 
@@ -67,98 +152,632 @@ pub struct Dispute {
     challenger: Address,
     defender: Address,
     status: u8, // 0: Pending, 1: InProgress, 2: Resolved
-    current_round: u8,
-    total_rounds: u8,
+    current_round: U256,
+    total_rounds: U256,
     timestamp: U256,
-    disputed_tx_hash: FixedBytes<32>,
+    assertion_id: FixedBytes<32>,
+    range_start: U256,
+    range_end: U256,
+    start_hash: FixedBytes<32>,
+    challenger_end_hash: FixedBytes<32>,
+    defender_end_hash: FixedBytes<32>,
+    challenger_stake: U256,
+    defender_stake: U256,
+    bond_deadline: U256,
+    clock_budget: U256,
+}
+
+// ABI tuple of `Dispute`, fields in declaration order
+pub type DisputeAbi = (
+    Address,
+    Address,
+    u8,
+    U256,
+    U256,
+    U256,
+    FixedBytes<32>,
+    U256,
+    U256,
+    FixedBytes<32>,
+    FixedBytes<32>,
+    FixedBytes<32>,
+    U256,
+    U256,
+    U256,
+    U256,
+);
+
+impl Dispute {
+    fn into_abi(self) -> DisputeAbi {
+        (
+            self.challenger,
+            self.defender,
+            self.status,
+            self.current_round,
+            self.total_rounds,
+            self.timestamp,
+            self.assertion_id,
+            self.range_start,
+            self.range_end,
+            self.start_hash,
+            self.challenger_end_hash,
+            self.defender_end_hash,
+            self.challenger_stake,
+            self.defender_stake,
+            self.bond_deadline,
+            self.clock_budget,
+        )
+    }
 }
 
 #[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
 pub struct ChallengeRound {
-    round: u8,
+    round: U256,
     bisection_point: U256,
     challenger_claim: FixedBytes<32>,
     defender_response: FixedBytes<32>,
     status: u8, // 0: Pending, 1: Completed
 }
 
-// Constants for event topic hashes
-const DISPUTE_INITIATED_EVENT: U256 = 
-    U256::from_be_bytes(hex!("c0ffc525a1c7ed42f5800d3456826e1ab57528228b4b78a5f4395f12acad581c"));
-const BISECTION_CHALLENGE_EVENT: U256 = 
-    U256::from_be_bytes(hex!("8ce8baccddca58f45e59c77062be74f8e3a1c8e309282ad8bb436f6d9e25008f"));
-const DISPUTE_RESOLVED_EVENT: U256 = 
-    U256::from_be_bytes(hex!("4c514279ce03d81bf6b1f1eb88b43c634d1d7e6ed95835c967e33b44c8ec8785"));
-
-// Helper function
-fn min(a: usize, b: usize) -> usize {
-    if a < b { a } else { b }
+// ABI tuple of `ChallengeRound`, fields in declaration order
+pub type ChallengeRoundAbi = (U256, U256, FixedBytes<32>, FixedBytes<32>, u8);
+
+impl ChallengeRound {
+    fn into_abi(self) -> ChallengeRoundAbi {
+        (
+            self.round,
+            self.bisection_point,
+            self.challenger_claim,
+            self.defender_response,
+            self.status,
+        )
+    }
+}
+
+// Assertion view: proposer, parent, state root, bond, challenge deadline,
+// status and open dispute count
+pub type AssertionAbi = (Address, FixedBytes<32>, FixedBytes<32>, U256, U256, u8, U256);
+
+// Canonical event signatures, shared with the frontend ABI
+sol! {
+    event DisputeInitiated(bytes32 indexed disputeId, address indexed challenger, address indexed defender);
+    event DefenderBondPosted(bytes32 indexed disputeId, address indexed defender, uint256 amount);
+    event BisectionChallenge(bytes32 indexed disputeId, uint256 round, uint256 bisectionPoint, bytes32 claimHash);
+    event DefenderResponse(bytes32 indexed disputeId, uint256 round, bytes32 responseHash, uint256 rangeStart, uint256 rangeEnd);
+    event OneStepProofVerified(bytes32 indexed disputeId, bytes32 postStateHash);
+    event DisputeResolved(bytes32 indexed disputeId, address indexed winner, uint8 outcome, uint8 reason);
+    event DisputeSettled(bytes32 indexed disputeId, address indexed winner, uint256 payout, uint256 fee);
+    event ConfigUpdated(uint256 indexed parameter, uint256 oldValue, uint256 newValue);
+    event ConfigBoundsUpdated(uint256 indexed parameter, uint256 min, uint256 max);
+    event ProtocolFeeUpdated(uint256 feeBps, address indexed recipient);
+    event TimeoutClaimed(bytes32 indexed disputeId, address indexed loser);
+    event AssertionPosted(bytes32 indexed assertionId, bytes32 indexed parentId, address indexed proposer, bytes32 stateRoot);
+    event AssertionConfirmed(bytes32 indexed assertionId, bytes32 stateRoot);
+    event AssertionInvalidated(bytes32 indexed assertionId, bytes32 indexed disputeId, address indexed challenger);
+    event DisputeCancelled(bytes32 indexed disputeId, address indexed challenger);
+    event MutualSettlement(bytes32 indexed disputeId, address indexed winner);
+    event VerifyingKeyRegistered(bytes32 indexed vkId, uint256 inputCount);
+}
+
+// Dispute status codes, see `Dispute::status`
+const STATUS_PENDING: u64 = 0;
+const STATUS_IN_PROGRESS: u64 = 1;
+const STATUS_RESOLVED: u64 = 2;
+
+// Outcome codes of a resolved dispute; Timeout covers every forfeit, the
+// winner is recorded alongside
+const OUTCOME_NONE: u8 = 0;
+const OUTCOME_CHALLENGER_WON: u8 = 1;
+const OUTCOME_DEFENDER_WON: u8 = 2;
+const OUTCOME_TIMEOUT: u8 = 3;
+const OUTCOME_CANCELLED: u8 = 4;
+
+// Why a dispute was resolved
+const REASON_NONE: u8 = 0;
+const REASON_BOND_MISSED: u8 = 1;
+const REASON_ONE_STEP_PROOF: u8 = 2;
+const REASON_CLOCK_EXPIRED: u8 = 3;
+// 4 was the dispute-wide challenge period, superseded by the clocks
+const REASON_ASSERTION_INVALIDATED: u8 = 5;
+const REASON_CANCELLED_BY_CHALLENGER: u8 = 6;
+const REASON_MUTUAL_SETTLEMENT: u8 = 7;
+
+// Assertion status codes; a pending assertion is invalidated by the first
+// challenger win or an invalid parent
+const ASSERTION_PENDING: u64 = 0;
+const ASSERTION_CONFIRMED: u64 = 1;
+const ASSERTION_INVALID: u64 = 2;
+
+// Protocol modes: classic state-hash bisection, or BoLD-style history
+// commitments over the states after steps 0..=n, bisected with prefix proofs
+const MODE_STATE_HASH: u64 = 0;
+const MODE_HISTORY: u64 = 1;
+
+// Round status codes, see `ChallengeRound::status`
+const ROUND_PENDING: u64 = 0;
+const ROUND_COMPLETED: u64 = 1;
+
+// Game parameter ids, see `ConfigUpdated::parameter`
+const CONFIG_STAKE: u64 = 0;
+const CONFIG_CHALLENGE_PERIOD: u64 = 1;
+const CONFIG_BOND_WINDOW: u64 = 2;
+const CONFIG_MAX_ROUNDS: u64 = 3;
+const CONFIG_CLOCK_BUDGET: u64 = 4;
+const CONFIG_ASSERTION_BOND: u64 = 5;
+
+// Defaults used until the owner changes a parameter
+const DEFAULT_STAKE: u64 = 100_000_000_000_000_000; // 0.1 ETH
+const DEFAULT_CHALLENGE_PERIOD: u64 = 86400; // 24 hours
+const DEFAULT_BOND_WINDOW: u64 = 3600; // 1 hour
+const DEFAULT_MAX_ROUNDS: u64 = 64; // traces of up to 2^64 steps
+const DEFAULT_CLOCK_BUDGET: u64 = 43200; // 12 hours per party
+const DEFAULT_ASSERTION_BOND: u64 = 1_000_000_000_000_000_000; // 1 ETH
+
+// EIP-712 domain and message types for mutual settlements
+const EIP712_DOMAIN_TYPE: &[u8] =
+    b"EIP712Domain(string name,string version,uint256 chainId,address verifyingContract)";
+const EIP712_NAME: &[u8] = b"ArbiProofSimulator";
+const EIP712_VERSION: &[u8] = b"1";
+const SETTLEMENT_TYPE: &[u8] = b"Settlement(bytes32 disputeId,address winner,uint256 deadline)";
+
+// ecrecover precompile, and the largest s it accepts from us (secp256k1n / 2)
+const ECRECOVER: Address = Address::with_last_byte(1);
+const SECP256K1N_HALF: U256 = U256::from_limbs([
+    0xdfe92f46681b20a0,
+    0x5d576e7357a4501d,
+    0xffffffffffffffff,
+    0x7fffffffffffffff,
+]);
+
+// Largest number of public inputs a registered verifying key may take
+const MAX_VK_INPUTS: usize = 32;
+
+// Upper bound on the page size of the enumeration views
+const MAX_PAGE_SIZE: u64 = 100;
+
+// Account allowed to initialize the contract, fixed at build time through
+// ARBIPROOF_DEPLOYER so initialization cannot be front-run after deployment;
+// when it is unset nobody can initialize
+#[cfg(not(test))]
+const DEPLOYER: Address = parse_address(option_env!("ARBIPROOF_DEPLOYER"));
+#[cfg(test)]
+const DEPLOYER: Address = testing::DEPLOYER;
+
+const fn parse_address(hex: Option<&str>) -> Address {
+    let Some(hex) = hex else {
+        return Address::ZERO;
+    };
+    let digits = hex.as_bytes();
+    let offset = if digits.len() == 42 && digits[0] == b'0' && digits[1] == b'x' { 2 } else { 0 };
+    assert!(digits.len() - offset == 40, "ARBIPROOF_DEPLOYER must be a 20-byte hex address");
+
+    const fn nibble(digit: u8) -> u8 {
+        match digit {
+            b'0'..=b'9' => digit - b'0',
+            b'a'..=b'f' => digit - b'a' + 10,
+            b'A'..=b'F' => digit - b'A' + 10,
+            _ => panic!("ARBIPROOF_DEPLOYER must be a 20-byte hex address"),
+        }
+    }
+
+    let mut bytes = [0u8; 20];
+    let mut i = 0;
+    while i < 20 {
+        bytes[i] = nibble(digits[offset + 2 * i]) << 4 | nibble(digits[offset + 2 * i + 1]);
+        i += 1;
+    }
+    Address::new(bytes)
 }
 
+// Protocol fee is expressed in basis points of the settled pot
+const BPS_DENOMINATOR: u64 = 10_000;
+const MAX_PROTOCOL_FEE_BPS: u64 = 1_000; // 10%
+
 #[external]
 impl ArbiProofSimulator {
-    pub fn constructor(&mut self) {
-        // Initialization logic if needed
+    pub fn constructor(&mut self, genesis_state_root: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        // Stylus has no deploy-time constructor, so only the build's deployer
+        // may claim ownership, and only once
+        if msg::sender() != DEPLOYER {
+            return Err("Only deployer can initialize".into());
+        }
+        if self.owner.get() != Address::ZERO {
+            return Err("Already initialized".into());
+        }
+        if genesis_state_root == FixedBytes::<32>::default() {
+            return Err("Empty genesis state root".into());
+        }
+        self.owner.set(msg::sender());
+        self.genesisStateRoot.set(genesis_state_root);
+        Ok(())
+    }
+
+    pub fn get_genesis_state_root(&self) -> FixedBytes<32> {
+        self.genesisStateRoot.get()
+    }
+
+    pub fn set_protocol_fee(&mut self, fee_bps: U256, recipient: Address) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+        if fee_bps > U256::from(MAX_PROTOCOL_FEE_BPS) {
+            return Err("Fee too high".into());
+        }
+        if fee_bps != U256::ZERO && recipient == Address::ZERO {
+            return Err("Invalid fee recipient".into());
+        }
+
+        self.protocolFeeBps.set(fee_bps);
+        self.feeRecipient.set(recipient);
+
+        evm::log(ProtocolFeeUpdated {
+            feeBps: fee_bps,
+            recipient,
+        });
+
+        Ok(())
+    }
+
+    pub fn get_protocol_fee(&self) -> (U256, Address) {
+        (self.protocolFeeBps.get(), self.feeRecipient.get())
+    }
+
+    pub fn set_stake(&mut self, stake: U256) -> Result<(), Vec<u8>> {
+        self.update_config(CONFIG_STAKE, stake)
+    }
+
+    pub fn set_challenge_period(&mut self, challenge_period: U256) -> Result<(), Vec<u8>> {
+        self.update_config(CONFIG_CHALLENGE_PERIOD, challenge_period)
+    }
+
+    pub fn set_bond_window(&mut self, bond_window: U256) -> Result<(), Vec<u8>> {
+        self.update_config(CONFIG_BOND_WINDOW, bond_window)
     }
 
+    pub fn set_max_rounds(&mut self, max_rounds: U256) -> Result<(), Vec<u8>> {
+        // Round counts beyond 256 can never be reached with a uint256 trace
+        if max_rounds > U256::from(256) {
+            return Err("Too many rounds".into());
+        }
+        self.update_config(CONFIG_MAX_ROUNDS, max_rounds)
+    }
+
+    pub fn set_clock_budget(&mut self, clock_budget: U256) -> Result<(), Vec<u8>> {
+        self.update_config(CONFIG_CLOCK_BUDGET, clock_budget)
+    }
+
+    pub fn set_assertion_bond(&mut self, assertion_bond: U256) -> Result<(), Vec<u8>> {
+        self.update_config(CONFIG_ASSERTION_BOND, assertion_bond)
+    }
+
+    // Lets disputes override `parameter` (stake, bond window or clock budget)
+    // with any value in [min, max]
+    pub fn set_config_bounds(&mut self, parameter: u8, min: U256, max: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+        let parameter = parameter as u64;
+        if !matches!(parameter, CONFIG_STAKE | CONFIG_BOND_WINDOW | CONFIG_CLOCK_BUDGET) {
+            return Err("Parameter cannot be overridden".into());
+        }
+        if min == U256::ZERO || min > max {
+            return Err("Invalid config bounds".into());
+        }
+
+        self.configMin.insert(U256::from(parameter), min);
+        self.configMax.insert(U256::from(parameter), max);
+
+        evm::log(ConfigBoundsUpdated {
+            parameter: U256::from(parameter),
+            min,
+            max,
+        });
+
+        Ok(())
+    }
+
+    pub fn get_config_bounds(&self, parameter: u8) -> (U256, U256) {
+        self.config_bounds(parameter as u64)
+    }
+
+    // Effective values: stake, challenge period, bond window, max rounds,
+    // clock budget, assertion bond
+    pub fn get_config(&self) -> (U256, U256, U256, U256, U256, U256) {
+        (
+            self.config_value(CONFIG_STAKE),
+            self.config_value(CONFIG_CHALLENGE_PERIOD),
+            self.config_value(CONFIG_BOND_WINDOW),
+            self.config_value(CONFIG_MAX_ROUNDS),
+            self.config_value(CONFIG_CLOCK_BUDGET),
+            self.config_value(CONFIG_ASSERTION_BOND),
+        )
+    }
+
+    // Posts a bonded state root on top of `parent_id`; a zero parent builds on
+    // genesis
     #[payable]
-    pub fn initiate_dispute(
+    pub fn post_assertion(
         &mut self,
-        tx_hash: FixedBytes<32>,
-        defender: Address,
+        parent_id: FixedBytes<32>,
+        state_root: FixedBytes<32>,
     ) -> Result<FixedBytes<32>, Vec<u8>> {
-        let caller = msg::sender();
+        let proposer = msg::sender();
         let value = msg::value();
 
-        // Require minimum stake
-        if value < U256::from(100000000000000000u64) { // 0.1 ETH
-            return Err("Insufficient stake".into());
+        if value < self.config_value(CONFIG_ASSERTION_BOND) {
+            return Err("Insufficient assertion bond".into());
         }
 
-        // Generate dispute ID using keccak256
-        let mut hasher = Keccak256::new();
-        hasher.update(tx_hash.as_slice());
-        hasher.update(caller.as_slice());
-        hasher.update(&U256::from(block::timestamp()).to_be_bytes::<32>());
-        let dispute_id = FixedBytes::<32>::from_slice(&hasher.finalize());
+        if state_root == FixedBytes::<32>::default() {
+            return Err("Empty state root".into());
+        }
 
-        // Store dispute fields individually
-        self.disputeChallenger.insert(dispute_id, caller);
-        self.disputeDefender.insert(dispute_id, defender);
-        self.disputeStatus.insert(dispute_id, U256::from(1)); // InProgress
-        self.disputeCurrentRound.insert(dispute_id, U256::ZERO);
-        self.disputeTotalRounds.insert(dispute_id, U256::from(5));
-        self.disputeTimestamp.insert(dispute_id, U256::from(block::timestamp()));
-        self.disputeTxHash.insert(dispute_id, tx_hash);
+        if parent_id != FixedBytes::<32>::default() {
+            if !self.assertion_exists(parent_id) {
+                return Err("Parent assertion not found".into());
+            }
+            if self.assertionStatus.get(parent_id) == U256::from(ASSERTION_INVALID) {
+                return Err("Parent assertion invalidated".into());
+            }
+        }
 
-        // Initialize rounds count
-        self.roundsCount.insert(dispute_id, U256::ZERO);
+        let assertion_id = Self::derive_assertion_id(parent_id, state_root, proposer);
+        if self.assertion_exists(assertion_id) {
+            return Err("Assertion already exists".into());
+        }
 
-        // Convert all topic data to FixedBytes<32> directly
-        let topic0: FixedBytes<32> = FixedBytes::from_slice(&DISPUTE_INITIATED_EVENT.to_be_bytes::<32>());
-        
-        // dispute_id is already FixedBytes<32>
-        
-        // Pad caller address to 32 bytes
-        let mut t2_bytes = [0u8; 32];
-        t2_bytes[12..].copy_from_slice(caller.as_slice());
-        let t2 = FixedBytes::from_slice(&t2_bytes);
-        
-        // Pad defender address to 32 bytes
-        let mut t3_bytes = [0u8; 32];
-        t3_bytes[12..].copy_from_slice(defender.as_slice());
-        let t3 = FixedBytes::from_slice(&t3_bytes);
-        
-        // Create a slice of FixedBytes<32> for topics
-        let topics: [FixedBytes<32>; 4] = [topic0, dispute_id, t2, t3];
-        
-        // Empty data array
-        let empty_data: [u8; 0] = [];
-        
-        // Corrected argument order: raw_log(topics, data)
-        let _ = evm::raw_log(&topics, &empty_data);
+        self.assertionProposer.insert(assertion_id, proposer);
+        self.assertionParent.insert(assertion_id, parent_id);
+        self.assertionStateRoot.insert(assertion_id, state_root);
+        self.assertionBond.insert(assertion_id, value);
+        self.assertionStatus.insert(assertion_id, U256::from(ASSERTION_PENDING));
 
-        Ok(dispute_id)
+        // Disputes can only be opened until the challenge window closes
+        let deadline = U256::from(block::timestamp()) + self.config_value(CONFIG_CHALLENGE_PERIOD);
+        self.assertionDeadline.insert(assertion_id, deadline);
+
+        evm::log(AssertionPosted {
+            assertionId: assertion_id.0,
+            parentId: parent_id.0,
+            proposer,
+            stateRoot: state_root.0,
+        });
+
+        Ok(assertion_id)
+    }
+
+    // Confirms an assertion that survived its challenge window and returns the
+    // bond to the proposer
+    pub fn confirm_assertion(&mut self, assertion_id: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        if !self.assertion_exists(assertion_id) {
+            return Err("Assertion not found".into());
+        }
+
+        if self.assertionStatus.get(assertion_id) != U256::from(ASSERTION_PENDING) {
+            return Err("Assertion not pending".into());
+        }
+
+        if U256::from(block::timestamp()) <= self.assertionDeadline.get(assertion_id) {
+            return Err("Assertion still in challenge window".into());
+        }
+
+        if self.assertionOpenCount.get(assertion_id) != U256::ZERO {
+            return Err("Assertion has open disputes".into());
+        }
+
+        // Assertions confirm in order along the chain
+        let parent_id = self.assertionParent.get(assertion_id);
+        if parent_id != FixedBytes::<32>::default()
+            && self.assertionStatus.get(parent_id) != U256::from(ASSERTION_CONFIRMED)
+        {
+            return Err("Parent assertion not confirmed".into());
+        }
+
+        self.assertionStatus.insert(assertion_id, U256::from(ASSERTION_CONFIRMED));
+
+        let bond = self.assertionBond.get(assertion_id);
+        self.assertionBond.insert(assertion_id, U256::ZERO);
+        if bond > U256::ZERO {
+            call::transfer_eth(self.assertionProposer.get(assertion_id), bond)?;
+        }
+
+        evm::log(AssertionConfirmed {
+            assertionId: assertion_id.0,
+            stateRoot: self.assertionStateRoot.get(assertion_id).0,
+        });
+
+        Ok(())
+    }
+
+    // Invalidates a pending assertion built on an invalidated parent; its bond
+    // is forfeited to the protocol
+    pub fn reject_assertion(&mut self, assertion_id: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        if !self.assertion_exists(assertion_id) {
+            return Err("Assertion not found".into());
+        }
+
+        if self.assertionStatus.get(assertion_id) != U256::from(ASSERTION_PENDING) {
+            return Err("Assertion not pending".into());
+        }
+
+        let parent_id = self.assertionParent.get(assertion_id);
+        if parent_id == FixedBytes::<32>::default()
+            || self.assertionStatus.get(parent_id) != U256::from(ASSERTION_INVALID)
+        {
+            return Err("Parent assertion not invalidated".into());
+        }
+
+        // No dispute brought it down, so the bond goes to the fee recipient,
+        // or to the owner while none is set
+        let mut recipient = self.feeRecipient.get();
+        if recipient == Address::ZERO {
+            recipient = self.owner.get();
+        }
+        if recipient == Address::ZERO {
+            return Err("Not initialized".into());
+        }
+        self.invalidate_assertion(assertion_id, FixedBytes::<32>::default(), recipient)
+    }
+
+    // Disputes the execution from the parent assertion's state root to this
+    // one's; the challenger claims `end_state_hash` instead, and proves both
+    // ends are the first and last leaves of `trace_root`
+    #[payable]
+    pub fn initiate_dispute(
+        &mut self,
+        assertion_id: FixedBytes<32>,
+        trace_length: U256,
+        end_state_hash: FixedBytes<32>,
+        trace_root: FixedBytes<32>,
+        start_proof: Vec<FixedBytes<32>>,
+        end_proof: Vec<FixedBytes<32>>,
+    ) -> Result<FixedBytes<32>, Vec<u8>> {
+        if !self.assertion_exists(assertion_id) {
+            return Err("Assertion not found".into());
+        }
+        Self::verify_trace_ends(
+            trace_root,
+            trace_length,
+            self.parent_state_root(assertion_id),
+            end_state_hash,
+            &start_proof,
+            &end_proof,
+        )?;
+        self.create_dispute(
+            assertion_id,
+            trace_length,
+            end_state_hash,
+            end_state_hash,
+            trace_root,
+            U256::ZERO,
+            U256::ZERO,
+            U256::ZERO,
+            MODE_STATE_HASH,
+        )
+    }
+
+    // Starts a dispute in history mode: `end_root` commits to the states after
+    // steps 0..=trace_length, the last being `end_state` on top of the
+    // `end_expansion` of the ones before; the agreed start commits to the
+    // parent assertion's state root alone
+    #[payable]
+    pub fn initiate_history_dispute(
+        &mut self,
+        assertion_id: FixedBytes<32>,
+        trace_length: U256,
+        end_root: FixedBytes<32>,
+        end_state: FixedBytes<32>,
+        end_expansion: Vec<FixedBytes<32>>,
+    ) -> Result<FixedBytes<32>, Vec<u8>> {
+        if trace_length >= U256::from(u64::MAX) {
+            return Err("Trace too long".into());
+        }
+        Self::verify_history_end(end_root, trace_length, end_state, &end_expansion)?;
+        self.create_dispute(
+            assertion_id,
+            trace_length,
+            end_root,
+            end_state,
+            FixedBytes::<32>::default(),
+            U256::ZERO,
+            U256::ZERO,
+            U256::ZERO,
+            MODE_HISTORY,
+        )
+    }
+
+    // Same as `initiate_dispute`, with this dispute's own stake (the value
+    // sent), clock budget and bond window, each within the owner's bounds;
+    // zero keeps the configured clock budget or bond window
+    #[payable]
+    #[allow(clippy::too_many_arguments)]
+    pub fn initiate_dispute_with_config(
+        &mut self,
+        assertion_id: FixedBytes<32>,
+        trace_length: U256,
+        end_state_hash: FixedBytes<32>,
+        trace_root: FixedBytes<32>,
+        start_proof: Vec<FixedBytes<32>>,
+        end_proof: Vec<FixedBytes<32>>,
+        clock_budget: U256,
+        bond_window: U256,
+    ) -> Result<FixedBytes<32>, Vec<u8>> {
+        if !self.assertion_exists(assertion_id) {
+            return Err("Assertion not found".into());
+        }
+        Self::verify_trace_ends(
+            trace_root,
+            trace_length,
+            self.parent_state_root(assertion_id),
+            end_state_hash,
+            &start_proof,
+            &end_proof,
+        )?;
+        self.create_dispute(
+            assertion_id,
+            trace_length,
+            end_state_hash,
+            end_state_hash,
+            trace_root,
+            msg::value(),
+            clock_budget,
+            bond_window,
+            MODE_STATE_HASH,
+        )
+    }
+
+    // Matches the challenger's stake and defends the assertion's state root
+    // with the defender's own `trace_root`, whose first and last leaves have
+    // to be the agreed start and the assertion's state root
+    #[payable]
+    pub fn post_defender_bond(
+        &mut self,
+        dispute_id: FixedBytes<32>,
+        trace_root: FixedBytes<32>,
+        start_proof: Vec<FixedBytes<32>>,
+        end_proof: Vec<FixedBytes<32>>,
+    ) -> Result<(), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+        if self.disputeMode.get(dispute_id) != U256::from(MODE_STATE_HASH) {
+            return Err("Not a state hash dispute".into());
+        }
+
+        let assertion_root = self.assertionStateRoot.get(self.disputeAssertionId.get(dispute_id));
+        Self::verify_trace_ends(
+            trace_root,
+            self.disputeRangeEnd.get(dispute_id),
+            self.disputeStartHash.get(dispute_id),
+            assertion_root,
+            &start_proof,
+            &end_proof,
+        )?;
+        self.disputeDefenderTraceRoot.insert(dispute_id, trace_root);
+        self.bond_defender(dispute_id, assertion_root)
+    }
+
+    // History-mode counterpart of `post_defender_bond`: `end_root` has to
+    // commit to trace_length + 1 states ending in the assertion's state root,
+    // on top of the `end_expansion` of the ones before
+    #[payable]
+    pub fn post_history_defender_bond(
+        &mut self,
+        dispute_id: FixedBytes<32>,
+        end_root: FixedBytes<32>,
+        end_expansion: Vec<FixedBytes<32>>,
+    ) -> Result<(), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+        if self.disputeMode.get(dispute_id) != U256::from(MODE_HISTORY) {
+            return Err("Not a history dispute".into());
+        }
+
+        let assertion_id = self.disputeAssertionId.get(dispute_id);
+        Self::verify_history_end(
+            end_root,
+            self.disputeRangeEnd.get(dispute_id),
+            self.assertionStateRoot.get(assertion_id),
+            &end_expansion,
+        )?;
+        self.bond_defender(dispute_id, end_root)
     }
 
     pub fn submit_bisection_challenge(
@@ -166,6 +785,8 @@ impl ArbiProofSimulator {
         dispute_id: FixedBytes<32>,
         bisection_point: U256,
         claim_hash: FixedBytes<32>,
+        claim_proof: Vec<FixedBytes<32>>,
+        claim_expansion: Vec<FixedBytes<32>>,
     ) -> Result<(), Vec<u8>> {
         // Fetch dispute data
         if !self.dispute_exists(dispute_id) {
@@ -173,9 +794,11 @@ impl ArbiProofSimulator {
         }
         
         let status = self.disputeStatus.get(dispute_id);
-        if status != U256::from(1) {
+        if status != U256::from(STATUS_IN_PROGRESS) {
             return Err("Dispute not in progress".into());
         }
+
+        self.ensure_assertion_live(dispute_id)?;
         
         let challenger = self.disputeChallenger.get(dispute_id);
         if msg::sender() != challenger {
@@ -184,145 +807,745 @@ impl ArbiProofSimulator {
 
         // Get current round and increment
         let mut current_round = self.disputeCurrentRound.get(dispute_id);
+
+        // Moves alternate: the defender has to answer the previous round first
+        if current_round > U256::ZERO
+            && self.roundStatus.getter(dispute_id).get(current_round) != U256::from(ROUND_COMPLETED)
+        {
+            return Err("Awaiting defender response".into());
+        }
+
+        let total_rounds = self.disputeTotalRounds.get(dispute_id);
+        if current_round >= total_rounds {
+            return Err("Too many rounds".into());
+        }
+
+        // The challenger must split the disputed range at its midpoint
+        let start = self.disputeRangeStart.get(dispute_id);
+        let end = self.disputeRangeEnd.get(dispute_id);
+        if end - start <= U256::from(1) {
+            return Err("Single step remains".into());
+        }
+        if bisection_point != start + (end - start) / U256::from(2) {
+            return Err("Bisection point is not the midpoint".into());
+        }
+
+        if self.disputeMode.get(dispute_id) == U256::from(MODE_HISTORY) {
+            // The mid-point commitment has to be a prefix of the challenger's
+            // current end commitment
+            history::verify_prefix_proof(
+                claim_hash,
+                bisection_point.to::<u64>() + 1,
+                self.disputeChallengerEndHash.get(dispute_id),
+                end.to::<u64>() + 1,
+                &claim_expansion,
+                &claim_proof,
+            )?;
+        } else {
+            // The claimed mid-state has to be the one committed to at initiation
+            let leaf = merkle::step_leaf(bisection_point, claim_hash);
+            if !merkle::verify(&claim_proof, self.disputeTraceRoot.get(dispute_id), leaf) {
+                return Err("Claim not in trace root".into());
+            }
+        }
+
+        self.charge_clock(dispute_id, challenger)?;
+
         current_round += U256::from(1);
         
-        // Update round data - use appropriate storage based on round index
-        match current_round.as_limbs()[0] {
-            1 => {
-                self.roundStatus0.insert(dispute_id, U256::ZERO);
-                self.roundBisectionPoint0.insert(dispute_id, bisection_point);
-                self.roundChallengerClaim0.insert(dispute_id, claim_hash);
-                self.roundDefenderResponse0.insert(dispute_id, FixedBytes::<32>::default());
-            },
-            2 => {
-                self.roundStatus1.insert(dispute_id, U256::ZERO);
-                self.roundBisectionPoint1.insert(dispute_id, bisection_point);
-                self.roundChallengerClaim1.insert(dispute_id, claim_hash);
-                self.roundDefenderResponse1.insert(dispute_id, FixedBytes::<32>::default());
-            },
-            3 => {
-                self.roundStatus2.insert(dispute_id, U256::ZERO);
-                self.roundBisectionPoint2.insert(dispute_id, bisection_point);
-                self.roundChallengerClaim2.insert(dispute_id, claim_hash);
-                self.roundDefenderResponse2.insert(dispute_id, FixedBytes::<32>::default());
-            },
-            4 => {
-                self.roundStatus3.insert(dispute_id, U256::ZERO);
-                self.roundBisectionPoint3.insert(dispute_id, bisection_point);
-                self.roundChallengerClaim3.insert(dispute_id, claim_hash);
-                self.roundDefenderResponse3.insert(dispute_id, FixedBytes::<32>::default());
-            },
-            5 => {
-                self.roundStatus4.insert(dispute_id, U256::ZERO);
-                self.roundBisectionPoint4.insert(dispute_id, bisection_point);
-                self.roundChallengerClaim4.insert(dispute_id, claim_hash);
-                self.roundDefenderResponse4.insert(dispute_id, FixedBytes::<32>::default());
-            },
-            _ => return Err("Too many rounds".into()),
-        }
+        // Update round data
+        self.roundStatus.setter(dispute_id).insert(current_round, U256::from(ROUND_PENDING));
+        self.roundBisectionPoint.setter(dispute_id).insert(current_round, bisection_point);
+        self.roundChallengerClaim.setter(dispute_id).insert(current_round, claim_hash);
+        self.roundDefenderResponse.setter(dispute_id).insert(current_round, FixedBytes::<32>::default());
         
         // Increment rounds count
         self.roundsCount.insert(dispute_id, current_round);
         
         // Update dispute current round
         self.disputeCurrentRound.insert(dispute_id, current_round);
-        
-        // Check if we reached the end of rounds
-        let total_rounds = self.disputeTotalRounds.get(dispute_id);
-        if current_round >= total_rounds {
-            self.disputeStatus.insert(dispute_id, U256::from(2)); // Resolved
-        }
 
-        // Convert topics to FixedBytes<32>
-        let evt_topic = FixedBytes::from_slice(&BISECTION_CHALLENGE_EVENT.to_be_bytes::<32>());
-        
-        // Convert current_round U256 to FixedBytes<32>
-        let t2 = FixedBytes::from_slice(&current_round.to_be_bytes::<32>());
-        
-        // Create topics array
-        let topics = [evt_topic, dispute_id, t2];
-        
-        // Get data as bytes
-        let data = bisection_point.to_be_bytes::<32>();
-        
-        // Corrected argument order: raw_log(topics, data)
-        let _ = evm::raw_log(&topics, &data);
+        evm::log(BisectionChallenge {
+            disputeId: dispute_id.0,
+            round: current_round,
+            bisectionPoint: bisection_point,
+            claimHash: claim_hash.0,
+        });
 
         Ok(())
     }
 
-    pub fn resolve_dispute(&mut self, dispute_id: FixedBytes<32>) -> Result<(), Vec<u8>> {
-        // Fetch dispute data
+    pub fn submit_defender_response(
+        &mut self,
+        dispute_id: FixedBytes<32>,
+        round: U256,
+        response_hash: FixedBytes<32>,
+        response_proof: Vec<FixedBytes<32>>,
+        response_expansion: Vec<FixedBytes<32>>,
+    ) -> Result<(), Vec<u8>> {
         if !self.dispute_exists(dispute_id) {
             return Err("Dispute not found".into());
         }
-        
+
         let status = self.disputeStatus.get(dispute_id);
-        if status != U256::from(1) {
-            return Err("Dispute not in progress or already resolved".into());
+        if status != U256::from(STATUS_IN_PROGRESS) {
+            return Err("Dispute not in progress".into());
         }
-        
-        let timestamp = self.disputeTimestamp.get(dispute_id);
-        if block::timestamp() <= timestamp.as_limbs()[0] + 86400 {
-            return Err("Dispute is still in challenge period".into());
+
+        self.ensure_assertion_live(dispute_id)?;
+
+        let defender = self.disputeDefender.get(dispute_id);
+        if msg::sender() != defender {
+            return Err("Only defender can respond".into());
         }
 
-        // Resolve dispute
-        self.disputeStatus.insert(dispute_id, U256::from(2)); // Resolved
-        
-        // Get challenger for the event
-        let challenger = self.disputeChallenger.get(dispute_id);
-        
-        // Create topics as FixedBytes<32>
-        let evt_topic = FixedBytes::from_slice(&DISPUTE_RESOLVED_EVENT.to_be_bytes::<32>());
-        
-        // Pad challenger address to 32 bytes
-        let mut t2_bytes = [0u8; 32];
-        t2_bytes[12..].copy_from_slice(challenger.as_slice());
-        let t2 = FixedBytes::from_slice(&t2_bytes);
-        
-        // Create topics array
-        let topics = [evt_topic, dispute_id, t2];
-        
-        // Empty data array
-        let empty_data: [u8; 0] = [];
-        
-        // Corrected argument order: raw_log(topics, data)
-        let _ = evm::raw_log(&topics, &empty_data);
-        
+        // Only the round opened by the latest bisection can be answered
+        let current_round = self.disputeCurrentRound.get(dispute_id);
+        if round == U256::ZERO || round != current_round {
+            return Err("Not the current round".into());
+        }
+        if self.roundStatus.getter(dispute_id).get(round) != U256::from(ROUND_PENDING) {
+            return Err("Round already answered".into());
+        }
+
+        if response_hash == FixedBytes::<32>::default() {
+            return Err("Empty response".into());
+        }
+
+        self.charge_clock(dispute_id, defender)?;
+
+        let bisection_point = self.roundBisectionPoint.getter(dispute_id).get(round);
+        let challenger_claim = self.roundChallengerClaim.getter(dispute_id).get(round);
+        let agrees = response_hash == challenger_claim;
+        if self.disputeMode.get(dispute_id) == U256::from(MODE_HISTORY) {
+            // In history mode a disagreeing defender has to stay on its own
+            // history as well
+            if !agrees {
+                history::verify_prefix_proof(
+                    response_hash,
+                    bisection_point.to::<u64>() + 1,
+                    self.disputeDefenderEndHash.get(dispute_id),
+                    self.disputeRangeEnd.get(dispute_id).to::<u64>() + 1,
+                    &response_expansion,
+                    &response_proof,
+                )?;
+            }
+        } else {
+            // The response has to be the mid-state of the defender's own trace
+            let leaf = merkle::step_leaf(bisection_point, response_hash);
+            if !merkle::verify(&response_proof, self.disputeDefenderTraceRoot.get(dispute_id), leaf) {
+                return Err("Response not in trace root".into());
+            }
+        }
+
+        // Agreeing with the challenger's mid-state moves the dispute into the
+        // upper half, disagreeing keeps it in the lower half
+        if agrees {
+            self.disputeRangeStart.insert(dispute_id, bisection_point);
+            self.disputeStartHash.insert(dispute_id, challenger_claim);
+        } else {
+            self.disputeRangeEnd.insert(dispute_id, bisection_point);
+            self.disputeChallengerEndHash.insert(dispute_id, challenger_claim);
+            self.disputeDefenderEndHash.insert(dispute_id, response_hash);
+        }
+
+        self.roundDefenderResponse.setter(dispute_id).insert(round, response_hash);
+        self.roundStatus.setter(dispute_id).insert(round, U256::from(ROUND_COMPLETED));
+
+        evm::log(DefenderResponse {
+            disputeId: dispute_id.0,
+            round,
+            responseHash: response_hash.0,
+            rangeStart: self.disputeRangeStart.get(dispute_id),
+            rangeEnd: self.disputeRangeEnd.get(dispute_id),
+        });
+
         Ok(())
     }
 
-    // View functions that construct the structs for return values only
-    pub fn get_dispute(&self, dispute_id: FixedBytes<32>) -> Result<String, Vec<u8>> {
+    pub fn submit_one_step_proof(
+        &mut self,
+        dispute_id: FixedBytes<32>,
+        pre_state: Bytes,
+        instruction: Bytes,
+        proof: Vec<FixedBytes<32>>,
+        history_expansion: Vec<FixedBytes<32>>,
+    ) -> Result<Address, Vec<u8>> {
         if !self.dispute_exists(dispute_id) {
             return Err("Dispute not found".into());
         }
-        
-        // Return a simple string representation instead of the full struct
-        // since we have type compatibility issues
-        let challenger = self.disputeChallenger.get(dispute_id);
-        let status = self.disputeStatus.get(dispute_id);
-        
-        Ok(format!("Dispute: id={:?}, challenger={:?}, status={}", 
-                 dispute_id, challenger, status))
-    }
 
-    pub fn get_challenge_rounds_count(&self, dispute_id: FixedBytes<32>) -> Result<U256, Vec<u8>> {
-        if !self.dispute_exists(dispute_id) {
-            return Err("Dispute not found".into());
+        let status = self.disputeStatus.get(dispute_id);
+        if status != U256::from(STATUS_IN_PROGRESS) {
+            return Err("Dispute not in progress".into());
         }
-        
-        // Return the count of rounds for this dispute
-        Ok(self.roundsCount.get(dispute_id))
-    }
 
-    pub fn dispute_exists(&self, dispute_id: FixedBytes<32>) -> bool {
-        // Check if the challenger address is set for this dispute
-        self.disputeChallenger.get(dispute_id) != Address::ZERO
-    }
+        self.ensure_assertion_live(dispute_id)?;
 
-    // Benchmarking function
+        let start = self.disputeRangeStart.get(dispute_id);
+        let end = self.disputeRangeEnd.get(dispute_id);
+        if end - start != U256::from(1) {
+            return Err("Dispute not narrowed to a single step".into());
+        }
+
+        // Once the challenger's clock has run out the defender wins by timeout
+        if self.clock_expired(dispute_id) {
+            return Err("Clock expired".into());
+        }
+
+        // The pre-state must be the one both parties agreed on
+        let pre_state_hash = FixedBytes::<32>::from_slice(&Keccak256::digest(pre_state.as_slice()));
+        let history_mode = self.disputeMode.get(dispute_id) == U256::from(MODE_HISTORY);
+        let pre_expansion = if history_mode {
+            // `history_expansion` commits to the states before the agreed one,
+            // which has to be the last leaf of the agreed start root
+            if history::tree_size(&history_expansion) != start.to::<u64>() {
+                return Err("History expansion does not match range start".into());
+            }
+            let expansion = history::append_leaf(&history_expansion, pre_state_hash)?;
+            if history::root(&expansion)? != self.disputeStartHash.get(dispute_id) {
+                return Err("Pre-state does not match agreed state".into());
+            }
+            expansion
+        } else {
+            if pre_state_hash != self.disputeStartHash.get(dispute_id) {
+                return Err("Pre-state does not match agreed state".into());
+            }
+            Vec::new()
+        };
+
+        // Replay the step and see whose claim it reproduces; in history mode
+        // the claims are the start root extended by the post-state
+        let post_state_hash = vm::execute_one_step(&pre_state, &instruction, &proof)?;
+        let post_claim = if history_mode {
+            history::root(&history::append_leaf(&pre_expansion, post_state_hash)?)?
+        } else {
+            post_state_hash
+        };
+        let winner = if post_claim == self.disputeChallengerEndHash.get(dispute_id) {
+            self.disputeChallenger.get(dispute_id)
+        } else if post_claim == self.disputeDefenderEndHash.get(dispute_id) {
+            self.disputeDefender.get(dispute_id)
+        } else {
+            return Err("Post-state matches neither claim".into());
+        };
+        let outcome = if winner == self.disputeChallenger.get(dispute_id) {
+            OUTCOME_CHALLENGER_WON
+        } else {
+            OUTCOME_DEFENDER_WON
+        };
+
+        evm::log(OneStepProofVerified {
+            disputeId: dispute_id.0,
+            postStateHash: post_state_hash.0,
+        });
+
+        self.finalize_dispute(dispute_id, winner, outcome, REASON_ONE_STEP_PROOF)?;
+
+        Ok(winner)
+    }
+
+    // Lets clients compute the post-state hash of a step before submitting it
+    pub fn compute_one_step(
+        pre_state: Bytes,
+        instruction: Bytes,
+        proof: Vec<FixedBytes<32>>,
+    ) -> Result<FixedBytes<32>, Vec<u8>> {
+        Ok(vm::execute_one_step(&pre_state, &instruction, &proof)?)
+    }
+
+    pub fn resolve_dispute(&mut self, dispute_id: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        // Fetch dispute data
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+        
+        let status = self.disputeStatus.get(dispute_id);
+
+        // Once one challenger has won, every other open dispute on the same
+        // assertion goes to its challenger as well
+        if status != U256::from(STATUS_RESOLVED) && self.assertion_invalidated(dispute_id) {
+            let challenger = self.disputeChallenger.get(dispute_id);
+            return self.finalize_dispute(
+                dispute_id,
+                challenger,
+                OUTCOME_CHALLENGER_WON,
+                REASON_ASSERTION_INVALIDATED,
+            );
+        }
+
+        if status == U256::from(STATUS_IN_PROGRESS) {
+            // Live games only time out on the clocks
+            return self.claim_timeout(dispute_id).map(|_| ());
+        }
+        if status != U256::from(STATUS_PENDING) {
+            return Err("Dispute already resolved".into());
+        }
+
+        // The challenger wins by default if the defender never bonded
+        let deadline = self.disputeBondDeadline.get(dispute_id);
+        if U256::from(block::timestamp()) <= deadline {
+            return Err("Defender can still post bond".into());
+        }
+
+        let challenger = self.disputeChallenger.get(dispute_id);
+        self.finalize_dispute(dispute_id, challenger, OUTCOME_TIMEOUT, REASON_BOND_MISSED)
+    }
+
+    // Ends the dispute in favour of the waiting party once the party to move
+    // has used up its clock
+    pub fn claim_timeout(&mut self, dispute_id: FixedBytes<32>) -> Result<Address, Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        let status = self.disputeStatus.get(dispute_id);
+        if status != U256::from(STATUS_IN_PROGRESS) {
+            return Err("Dispute not in progress".into());
+        }
+
+        self.ensure_assertion_live(dispute_id)?;
+
+        if !self.clock_expired(dispute_id) {
+            return Err("Clock has not run out".into());
+        }
+
+        let loser = self.turn_of(dispute_id);
+        let winner = self.opponent_of(dispute_id, loser);
+
+        evm::log(TimeoutClaimed {
+            disputeId: dispute_id.0,
+            loser,
+        });
+
+        self.finalize_dispute(dispute_id, winner, OUTCOME_TIMEOUT, REASON_CLOCK_EXPIRED)?;
+
+        Ok(winner)
+    }
+
+    // Lets the challenger withdraw before the defender has bonded; the stake
+    // comes back minus the protocol fee
+    pub fn cancel_dispute(&mut self, dispute_id: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        let status = self.disputeStatus.get(dispute_id);
+        if status != U256::from(STATUS_PENDING) {
+            return Err("Dispute not pending".into());
+        }
+
+        let challenger = self.disputeChallenger.get(dispute_id);
+        if msg::sender() != challenger {
+            return Err("Only challenger can cancel".into());
+        }
+
+        evm::log(DisputeCancelled {
+            disputeId: dispute_id.0,
+            challenger,
+        });
+
+        self.finalize_dispute(dispute_id, Address::ZERO, OUTCOME_CANCELLED, REASON_CANCELLED_BY_CHALLENGER)
+    }
+
+    // Ends the dispute with the outcome both parties signed off on, see
+    // `settlement_digest` for the message they sign
+    pub fn settle_mutually(
+        &mut self,
+        dispute_id: FixedBytes<32>,
+        winner: Address,
+        deadline: U256,
+        challenger_signature: Bytes,
+        defender_signature: Bytes,
+    ) -> Result<(), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        let status = self.disputeStatus.get(dispute_id);
+        if status == U256::from(STATUS_RESOLVED) {
+            return Err("Dispute already resolved".into());
+        }
+
+        self.ensure_assertion_live(dispute_id)?;
+
+        if U256::from(block::timestamp()) > deadline {
+            return Err("Settlement expired".into());
+        }
+
+        let challenger = self.disputeChallenger.get(dispute_id);
+        let defender = self.disputeDefender.get(dispute_id);
+        let outcome = if winner == challenger {
+            OUTCOME_CHALLENGER_WON
+        } else if winner == defender {
+            OUTCOME_DEFENDER_WON
+        } else {
+            return Err("Winner must be a party".into());
+        };
+
+        let digest = self.settlement_digest(dispute_id, winner, deadline);
+        if self.recover_signer(digest, &challenger_signature)? != challenger {
+            return Err("Invalid challenger signature".into());
+        }
+        if self.recover_signer(digest, &defender_signature)? != defender {
+            return Err("Invalid defender signature".into());
+        }
+
+        evm::log(MutualSettlement {
+            disputeId: dispute_id.0,
+            winner,
+        });
+
+        self.finalize_dispute(dispute_id, winner, outcome, REASON_MUTUAL_SETTLEMENT)
+    }
+
+    // EIP-712 digest of Settlement(disputeId, winner, deadline) under this
+    // contract's domain
+    pub fn settlement_digest(&self, dispute_id: FixedBytes<32>, winner: Address, deadline: U256) -> FixedBytes<32> {
+        let mut domain = Keccak256::new();
+        domain.update(Keccak256::digest(EIP712_DOMAIN_TYPE));
+        domain.update(Keccak256::digest(EIP712_NAME));
+        domain.update(Keccak256::digest(EIP712_VERSION));
+        domain.update(U256::from(block::chainid()).to_be_bytes::<32>());
+        domain.update(contract::address().into_word());
+
+        let mut message = Keccak256::new();
+        message.update(Keccak256::digest(SETTLEMENT_TYPE));
+        message.update(dispute_id.as_slice());
+        message.update(winner.into_word());
+        message.update(deadline.to_be_bytes::<32>());
+
+        let mut hasher = Keccak256::new();
+        hasher.update(b"\x19\x01");
+        hasher.update(domain.finalize());
+        hasher.update(message.finalize());
+        FixedBytes::<32>::from_slice(&hasher.finalize())
+    }
+
+    // View functions that construct the structs for return values only
+    pub fn get_dispute(&self, dispute_id: FixedBytes<32>) -> Result<DisputeAbi, Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        Ok(self.load_dispute(dispute_id).into_abi())
+    }
+
+    pub fn get_challenge_round(
+        &self,
+        dispute_id: FixedBytes<32>,
+        round: U256,
+    ) -> Result<ChallengeRoundAbi, Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        // Rounds are 1-based and only exist once the challenger opened them
+        if round == U256::ZERO || round > self.roundsCount.get(dispute_id) {
+            return Err("Round not found".into());
+        }
+
+        Ok(self.load_challenge_round(dispute_id, round).into_abi())
+    }
+
+    pub fn get_challenge_rounds(&self, dispute_id: FixedBytes<32>) -> Result<Vec<ChallengeRoundAbi>, Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        let count = self.roundsCount.get(dispute_id).as_limbs()[0];
+        Ok((1..=count)
+            .map(|round| self.load_challenge_round(dispute_id, U256::from(round)).into_abi())
+            .collect())
+    }
+
+    pub fn get_dispute_range(&self, dispute_id: FixedBytes<32>) -> Result<(U256, U256), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        // A single step remains once end - start == 1
+        Ok((
+            self.disputeRangeStart.get(dispute_id),
+            self.disputeRangeEnd.get(dispute_id),
+        ))
+    }
+
+    pub fn get_dispute_claims(
+        &self,
+        dispute_id: FixedBytes<32>,
+    ) -> Result<(FixedBytes<32>, FixedBytes<32>, FixedBytes<32>, FixedBytes<32>, FixedBytes<32>), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        // Agreed start hash, the challenger's and defender's end hashes, and
+        // the challenger's and defender's trace roots
+        Ok((
+            self.disputeStartHash.get(dispute_id),
+            self.disputeChallengerEndHash.get(dispute_id),
+            self.disputeDefenderEndHash.get(dispute_id),
+            self.disputeTraceRoot.get(dispute_id),
+            self.disputeDefenderTraceRoot.get(dispute_id),
+        ))
+    }
+
+    // MODE_STATE_HASH or MODE_HISTORY
+    pub fn get_dispute_mode(&self, dispute_id: FixedBytes<32>) -> Result<u8, Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        Ok(self.disputeMode.get(dispute_id).to::<u8>())
+    }
+
+    pub fn get_challenge_rounds_count(&self, dispute_id: FixedBytes<32>) -> Result<U256, Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+        
+        // Return the count of rounds for this dispute
+        Ok(self.roundsCount.get(dispute_id))
+    }
+
+    pub fn get_dispute_stakes(&self, dispute_id: FixedBytes<32>) -> Result<(U256, U256), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        // Both are zero once the dispute has been settled
+        Ok((
+            self.disputeChallengerStake.get(dispute_id),
+            self.disputeDefenderStake.get(dispute_id),
+        ))
+    }
+
+    // Total rounds, clock budget and bond deadline of a dispute
+    pub fn get_dispute_config(&self, dispute_id: FixedBytes<32>) -> Result<(U256, U256, U256), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        Ok((
+            self.disputeTotalRounds.get(dispute_id),
+            self.disputeClockBudget.get(dispute_id),
+            self.disputeBondDeadline.get(dispute_id),
+        ))
+    }
+
+    // Outcome, reason and winner; all zero until the dispute is resolved
+    pub fn get_dispute_outcome(&self, dispute_id: FixedBytes<32>) -> Result<(u8, u8, Address), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        Ok((
+            self.disputeOutcome.get(dispute_id).to::<u8>(),
+            self.disputeReason.get(dispute_id).to::<u8>(),
+            self.disputeWinner.get(dispute_id),
+        ))
+    }
+
+    // Time left on the challenger's and defender's clocks, counting the
+    // current turn, and the party expected to move next
+    pub fn get_dispute_clocks(&self, dispute_id: FixedBytes<32>) -> Result<(U256, U256, Address), Vec<u8>> {
+        if !self.dispute_exists(dispute_id) {
+            return Err("Dispute not found".into());
+        }
+
+        let challenger = self.disputeChallenger.get(dispute_id);
+        let defender = self.disputeDefender.get(dispute_id);
+        let turn = if self.disputeStatus.get(dispute_id) == U256::from(STATUS_IN_PROGRESS) {
+            self.turn_of(dispute_id)
+        } else {
+            Address::ZERO
+        };
+
+        Ok((
+            self.clock_remaining(dispute_id, challenger),
+            self.clock_remaining(dispute_id, defender),
+            turn,
+        ))
+    }
+
+    // Proposer, parent, state root, bond, challenge deadline, status and
+    // number of open disputes of an assertion
+    pub fn get_assertion(
+        &self,
+        assertion_id: FixedBytes<32>,
+    ) -> Result<AssertionAbi, Vec<u8>> {
+        if !self.assertion_exists(assertion_id) {
+            return Err("Assertion not found".into());
+        }
+
+        Ok((
+            self.assertionProposer.get(assertion_id),
+            self.assertionParent.get(assertion_id),
+            self.assertionStateRoot.get(assertion_id),
+            self.assertionBond.get(assertion_id),
+            self.assertionDeadline.get(assertion_id),
+            self.assertionStatus.get(assertion_id).to::<u8>(),
+            self.assertionOpenCount.get(assertion_id),
+        ))
+    }
+
+    // Id `proposer` gets for posting `state_root` on top of `parent_id`
+    pub fn compute_assertion_id(
+        &self,
+        parent_id: FixedBytes<32>,
+        state_root: FixedBytes<32>,
+        proposer: Address,
+    ) -> FixedBytes<32> {
+        Self::derive_assertion_id(parent_id, state_root, proposer)
+    }
+
+    pub fn assertion_exists(&self, assertion_id: FixedBytes<32>) -> bool {
+        self.assertionProposer.get(assertion_id) != Address::ZERO
+    }
+
+    // Disputes on the assertion that are still waiting to be resolved
+    pub fn get_open_disputes(&self, assertion_id: FixedBytes<32>) -> Vec<FixedBytes<32>> {
+        let count = self.assertionOpenCount.get(assertion_id).as_limbs()[0];
+        (1..=count)
+            .map(|slot| self.assertionOpenDisputes.getter(assertion_id).get(U256::from(slot)))
+            .collect()
+    }
+
+    // Id the challenger's next dispute over `assertion_id` will get
+    pub fn compute_dispute_id(&self, assertion_id: FixedBytes<32>, challenger: Address) -> FixedBytes<32> {
+        Self::derive_dispute_id(assertion_id, challenger, self.disputeNonce.get(challenger))
+    }
+
+    pub fn get_dispute_count(&self) -> U256 {
+        self.disputeCount.get()
+    }
+
+    // Dispute ids in creation order, at most MAX_PAGE_SIZE per call
+    pub fn get_disputes(&self, offset: U256, limit: U256) -> Vec<FixedBytes<32>> {
+        Self::page(self.disputeCount.get(), offset, limit)
+            .map(|index| self.disputeIds.get(U256::from(index)))
+            .collect()
+    }
+
+    pub fn get_challenger_dispute_count(&self, challenger: Address) -> U256 {
+        self.disputeNonce.get(challenger)
+    }
+
+    pub fn get_challenger_disputes(&self, challenger: Address, offset: U256, limit: U256) -> Vec<FixedBytes<32>> {
+        Self::page(self.disputeNonce.get(challenger), offset, limit)
+            .map(|index| self.challengerDisputes.getter(challenger).get(U256::from(index)))
+            .collect()
+    }
+
+    pub fn get_defender_dispute_count(&self, defender: Address) -> U256 {
+        self.defenderDisputeCount.get(defender)
+    }
+
+    pub fn get_defender_disputes(&self, defender: Address, offset: U256, limit: U256) -> Vec<FixedBytes<32>> {
+        Self::page(self.defenderDisputeCount.get(defender), offset, limit)
+            .map(|index| self.defenderDisputes.getter(defender).get(U256::from(index)))
+            .collect()
+    }
+
+    // Number of pending, in-progress and resolved disputes
+    pub fn get_status_counts(&self) -> (U256, U256, U256) {
+        (
+            self.statusCount.get(U256::from(STATUS_PENDING)),
+            self.statusCount.get(U256::from(STATUS_IN_PROGRESS)),
+            self.statusCount.get(U256::from(STATUS_RESOLVED)),
+        )
+    }
+
+    pub fn dispute_exists(&self, dispute_id: FixedBytes<32>) -> bool {
+        // Check if the challenger address is set for this dispute
+        self.disputeChallenger.get(dispute_id) != Address::ZERO
+    }
+
+    // Stores a Groth16 verifying key (see groth16.rs for the word layout)
+    // under the keccak256 of its words; anyone may register one
+    pub fn register_verifying_key(&mut self, vk: Vec<U256>) -> Result<FixedBytes<32>, Vec<u8>> {
+        let input_count = groth16::input_count(vk.len()).ok_or("Malformed verifying key")?;
+        if input_count > MAX_VK_INPUTS {
+            return Err("Too many public inputs".into());
+        }
+        if vk.iter().any(|word| *word >= groth16::FIELD_MODULUS) {
+            return Err("Verifying key coordinate out of range".into());
+        }
+
+        let vk_id = Self::derive_vk_id(&vk);
+        if !self.vkLength.get(vk_id).is_zero() {
+            return Err("Verifying key already registered".into());
+        }
+
+        self.vkLength.insert(vk_id, U256::from(vk.len()));
+        let mut words = self.vkWords.setter(vk_id);
+        for (index, word) in vk.iter().enumerate() {
+            words.insert(U256::from(index), *word);
+        }
+
+        evm::log(VerifyingKeyRegistered {
+            vkId: vk_id.0,
+            inputCount: U256::from(input_count),
+        });
+
+        Ok(vk_id)
+    }
+
+    pub fn get_verifying_key(&self, vk_id: FixedBytes<32>) -> Vec<U256> {
+        self.load_verifying_key(vk_id)
+    }
+
+    // Checks a Groth16 proof (A | B | C, 8 words) against a registered key
+    // with the ecAdd, ecMul and ecPairing precompiles
+    pub fn verify_groth16(
+        &self,
+        vk_id: FixedBytes<32>,
+        proof: Vec<U256>,
+        public_inputs: Vec<U256>,
+    ) -> Result<bool, Vec<u8>> {
+        let vk = self.load_verifying_key(vk_id);
+        if vk.is_empty() {
+            return Err("Unknown verifying key".into());
+        }
+        Ok(groth16::verify(self, &vk, &proof, &public_inputs)?)
+    }
+
+    // FRI low-degree test over Goldilocks, see fri.rs for the proof layout;
+    // soundness is roughly num_queries * log_blowup bits
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_fri(
+        &self,
+        log_degree: u8,
+        log_blowup: u8,
+        num_queries: u8,
+        roots: Vec<FixedBytes<32>>,
+        final_value: u64,
+        values: Vec<u64>,
+        paths: Vec<FixedBytes<32>>,
+    ) -> Result<bool, Vec<u8>> {
+        let proof = fri::FriProof {
+            roots,
+            final_value,
+            values,
+            paths,
+        };
+        Ok(fri::verify(log_degree.into(), log_blowup.into(), num_queries.into(), &proof)?)
+    }
+
+    // Result and gas used by `verify_groth16`, including the key load
+    pub fn benchmark_groth16(
+        &self,
+        vk_id: FixedBytes<32>,
+        proof: Vec<U256>,
+        public_inputs: Vec<U256>,
+    ) -> Result<(bool, U256), Vec<u8>> {
+        let start_gas = evm::gas_left();
+        let valid = self.verify_groth16(vk_id, proof, public_inputs)?;
+        let gas_used = start_gas - evm::gas_left();
+        Ok((valid, U256::from(gas_used)))
+    }
+
+    // Benchmarking function
     pub fn benchmark_step_verification(&mut self, step_verification_id: FixedBytes<32>) -> U256 {
         let current_count = self.benchmarkData.get(step_verification_id);
             
@@ -376,17 +1599,67 @@ impl ArbiProofSimulator {
         let gas_used = start_gas - evm::gas_left();
         results.push(("state_transition_stylus".to_string(), U256::from(gas_used)));
         
-        // 5. Add ZK-proof verification benchmark (Groth16 specifically mentioned in challenge)
+        // 5. Groth16 verification of a one-input proof over BN254
         let start_gas = evm::gas_left();
         self.benchmark_groth16_verification();
         let gas_used = start_gas - evm::gas_left();
         results.push(("groth16_verification_stylus".to_string(), U256::from(gas_used)));
+
+        // 6. FRI verification, pure computation with no precompiles; the
+        // proof is generated before the meter starts
+        let (log_degree, log_blowup, queries, proof) = fri::benchmark_instance();
+        let start_gas = evm::gas_left();
+        let _ = fri::verify(log_degree, log_blowup, queries, &proof);
+        let gas_used = start_gas - evm::gas_left();
+        results.push(("fri_verification_stylus".to_string(), U256::from(gas_used)));
         
-        // 6. Add financial computation benchmark (options pricing mentioned in challenge)
+        // 7. Add financial computation benchmark (options pricing mentioned in challenge)
         let start_gas = evm::gas_left();
         self.benchmark_black_scholes();
         let gas_used = start_gas - evm::gas_left();
         results.push(("options_pricing_stylus".to_string(), U256::from(gas_used)));
+
+        // 8. All five Greeks of the same option
+        let wad = U256::from(fixed::WAD as u128);
+        let hundred = U256::from(100) * wad;
+        let (rate, vol) = (wad / U256::from(20), wad / U256::from(5));
+        let start_gas = evm::gas_left();
+        let _ = self.option_greeks(hundred, hundred, rate, vol, wad, true);
+        let gas_used = start_gas - evm::gas_left();
+        results.push(("option_greeks_stylus".to_string(), U256::from(gas_used)));
+
+        // 9. Implied volatility recovered from a 10.45 call price (vol = 20%)
+        let price = U256::from(10_450_583_572_185_566_781u128);
+        let start_gas = evm::gas_left();
+        let _ = self.implied_volatility(price, hundred, hundred, rate, wad, true);
+        let gas_used = start_gas - evm::gas_left();
+        results.push(("implied_volatility_stylus".to_string(), U256::from(gas_used)));
+
+        // 10. Yield allocation over the five frontend strategies (lending,
+        // staking, LP provision, yield farming, options writing), risk 1-5,
+        // with an average risk budget of 3
+        let percent = wad / U256::from(100);
+        let apys = [35, 64, 82, 128, 185].map(|tenths| U256::from(tenths) * percent / U256::from(10)).to_vec();
+        let risks = (1..=5u64).map(|risk| U256::from(risk) * wad).collect();
+        let capacities = vec![U256::from(4_000) * wad; 5];
+        let start_gas = evm::gas_left();
+        let _ = self.optimize_yield(U256::from(10_000) * wad, apys, risks, capacities, U256::from(3) * wad);
+        let gas_used = start_gas - evm::gas_left();
+        results.push(("yield_optimization_stylus".to_string(), U256::from(gas_used)));
+
+        // 11. Monte Carlo over 64 paths of 12 monthly steps, for an Asian call
+        // and an up-and-out call at 130
+        let seed = FixedBytes::<32>::from([7u8; 32]);
+        let start_gas = evm::gas_left();
+        let _ = self.price_asian_option(hundred, hundred, rate, vol, wad, true, 64, 12, seed);
+        let gas_used = start_gas - evm::gas_left();
+        results.push(("monte_carlo_asian_stylus".to_string(), U256::from(gas_used)));
+
+        let barrier = U256::from(130) * wad;
+        let start_gas = evm::gas_left();
+        let _ = self.price_barrier_option(hundred, hundred, barrier, 0, rate, vol, wad, true, 64, 12, seed);
+        let gas_used = start_gas - evm::gas_left();
+        results.push(("monte_carlo_barrier_stylus".to_string(), U256::from(gas_used)));
         
         results
     }
@@ -401,7 +1674,7 @@ impl ArbiProofSimulator {
         let mut hasher = Keccak256::new();
         hasher.update(tx_hash.as_slice());
         hasher.update(caller.as_slice());
-        hasher.update(&U256::from(1234567890u64).to_be_bytes::<32>());
+        hasher.update(U256::from(1234567890u64).to_be_bytes::<32>());
         FixedBytes::<32>::from_slice(&hasher.finalize())
     }
     
@@ -414,8 +1687,8 @@ impl ArbiProofSimulator {
         let mut result = false;
         for i in 0..100 {
             let mut hasher = Keccak256::new();
-            hasher.update(&bisection_point.to_be_bytes::<32>());
-            hasher.update(&U256::from(i).to_be_bytes::<32>());
+            hasher.update(bisection_point.to_be_bytes::<32>());
+            hasher.update(U256::from(i).to_be_bytes::<32>());
             let hash = FixedBytes::<32>::from_slice(&hasher.finalize());
             if hash.as_slice()[0] < 10 {
                 result = true;
@@ -430,7 +1703,7 @@ impl ArbiProofSimulator {
         let mut result = false;
         for i in 0..50 {
             let mut hasher = Keccak256::new();
-            hasher.update(&[i as u8; 100]);
+            hasher.update([i as u8; 100]);
             let hash = hasher.finalize();
             if hash[0] < 10 && hash[1] < 10 {
                 result = true;
@@ -446,8 +1719,8 @@ impl ArbiProofSimulator {
         for i in 0..30 {
             let step_value = U256::from(i * i);
             let mut hasher = Keccak256::new();
-            hasher.update(&step_value.to_be_bytes::<32>());
-            hasher.update(&total.to_be_bytes::<32>());
+            hasher.update(step_value.to_be_bytes::<32>());
+            hasher.update(total.to_be_bytes::<32>());
             let result = FixedBytes::<32>::from_slice(&hasher.finalize());
             // Fixed: using as_slice() instead of to_be_bytes()
             total += U256::from_be_bytes::<32>(result.as_slice().try_into().unwrap());
@@ -456,78 +1729,184 @@ impl ArbiProofSimulator {
     }
 
     fn benchmark_groth16_verification(&mut self) -> bool {
-        // Simulate simplified Groth16 verification
-        // In a real implementation, you would use a ZK library
-        let mut result = false;
-        
-        // Simulate verification with matrix multiplication
-        let matrix1 = [1u8, 2u8, 3u8, 4u8, 5u8, 6u8, 7u8, 8u8, 9u8];
-        let matrix2 = [9u8, 8u8, 7u8, 6u8, 5u8, 4u8, 3u8, 2u8, 1u8];
-        let mut output = [0u8; 9];
-        
-        // 3x3 matrix multiplication (computationally intensive)
-        for i in 0..3 {
-            for j in 0..3 {
-                let mut sum = 0u8;
-                for k in 0..3 {
-                    sum = sum.wrapping_add(matrix1[i*3+k].wrapping_mul(matrix2[k*3+j]));
-                }
-                output[i*3+j] = sum;
-            }
-        }
-        
-        // Hash the result
-        let mut hasher = Keccak256::new();
-        hasher.update(&output);
-        let hash = hasher.finalize();
-        
-        if hash[0] < 128 {
-            result = true;
-        }
-        
-        result
+        // Full pairing check of a one-input proof through the BN254 precompiles
+        let (vk, proof, inputs) = groth16::benchmark_instance();
+        groth16::verify(&*self, &vk, &proof, &inputs).unwrap_or(false)
     }
 
     fn benchmark_black_scholes(&mut self) -> U256 {
-        // Simplified Black-Scholes option pricing algorithm with explicit type annotations
-        // S: stock price, K: strike price, r: risk-free rate, sigma: volatility, T: time to maturity
-        
-        let s: f32 = 100.0; // Stock price
-        let k: f32 = 100.0; // Strike price
-        let r: f32 = 0.05; // Risk-free rate
-        let sigma: f32 = 0.2; // Volatility
-        let t: f32 = 1.0; // Time to maturity (1 year)
-        
-        // Calculate d1 and d2 (simplified)
-        let mut d1: f32 = 0.0;
-        let mut d2: f32 = 0.0;
-        
-        // Perform 50 iterations to simulate computational intensity
-        for _i in 0..50 {
-            // ln(S/K) + (r + sigma^2/2)*T
-            let numerator = (s/k).ln() + (r + 0.5 * sigma * sigma) * t;
-            // sigma * sqrt(T)
-            let denominator = sigma * t.sqrt();
-            
-            d1 = numerator / denominator;
-            d2 = d1 - sigma * t.sqrt();
-            
-            // Use approximation for cumulative normal distribution
-            let nd1 = 0.5 * (1.0 + (d1 / (1.0 + 0.2316419 * d1.abs()).sqrt()));
-            let nd2 = 0.5 * (1.0 + (d2 / (1.0 + 0.2316419 * d2.abs()).sqrt()));
-            
-            // Calculate call option price (simplified)
-            let call = s * nd1 - k * (-r * t).exp() * nd2;
-            
-            // Use the result to prevent optimizer from removing the calculation
-            if call > 0.0 {
-                d1 = d1 + 0.000001;
-            }
+        // At-the-money one-year call: S = K = 100, r = 5%, vol = 20%
+        let wad = U256::from(fixed::WAD as u128);
+        let hundred = U256::from(100) * wad;
+        self.price_option(hundred, hundred, wad / U256::from(20), wad / U256::from(5), wad, true)
+            .unwrap_or_default()
+    }
+
+    // Black-Scholes price of a European option. All inputs and the price are
+    // 18-decimal fixed point: rate and vol annualized (5e16 = 5%), time in
+    // years; see options.rs for the accepted ranges and error bound
+    pub fn price_option(
+        &self,
+        spot: U256,
+        strike: U256,
+        rate: U256,
+        vol: U256,
+        time: U256,
+        is_call: bool,
+    ) -> Result<U256, Vec<u8>> {
+        let market = options::Market::new(spot, strike, rate, vol, time)?;
+        Ok(U256::from(market.price(is_call)? as u128))
+    }
+
+    // Delta, gamma, vega, theta and rho of the option priced by
+    // `price_option`, signed 18-decimal; vega and rho are per 1.00 of vol
+    // and rate, theta per year
+    pub fn option_greeks(
+        &self,
+        spot: U256,
+        strike: U256,
+        rate: U256,
+        vol: U256,
+        time: U256,
+        is_call: bool,
+    ) -> Result<(i128, i128, i128, i128, i128), Vec<u8>> {
+        let market = options::Market::new(spot, strike, rate, vol, time)?;
+        let greeks = market.greeks(is_call)?;
+        Ok((greeks.delta, greeks.gamma, greeks.vega, greeks.theta, greeks.rho))
+    }
+
+    // Volatility at which `price_option` returns `price`, 18-decimal
+    pub fn implied_volatility(
+        &self,
+        price: U256,
+        spot: U256,
+        strike: U256,
+        rate: U256,
+        time: U256,
+        is_call: bool,
+    ) -> Result<U256, Vec<u8>> {
+        let market = options::Market::without_vol(spot, strike, rate, time)?;
+        if price > U256::from(options::MAX_PRICE as u128) {
+            return Err("Price out of range".into());
         }
-        
-        // Convert the result to U256
-        let result = (d1 * 1000.0) as u64;
-        U256::from(result)
+        let vol = market.implied_vol(price.to::<u128>() as i128, is_call)?;
+        Ok(U256::from(vol as u128))
+    }
+
+    // Monte Carlo price and standard error of an arithmetic-average Asian
+    // option monitored at `steps` equal intervals; inputs as `price_option`,
+    // and the same seed always gives the same paths
+    #[allow(clippy::too_many_arguments)]
+    pub fn price_asian_option(
+        &self,
+        spot: U256,
+        strike: U256,
+        rate: U256,
+        vol: U256,
+        time: U256,
+        is_call: bool,
+        paths: u32,
+        steps: u32,
+        seed: FixedBytes<32>,
+    ) -> Result<(U256, U256), Vec<u8>> {
+        let market = options::Market::new(spot, strike, rate, vol, time)?;
+        let payoff = montecarlo::Payoff::Asian { is_call };
+        let (price, std_error) = montecarlo::price(&market, payoff, paths, steps, seed)?;
+        Ok((U256::from(price as u128), U256::from(std_error as u128)))
+    }
+
+    // Monte Carlo price and standard error of a discretely monitored barrier
+    // option; `barrier_type` is up-and-out (0), down-and-out (1),
+    // up-and-in (2) or down-and-in (3)
+    #[allow(clippy::too_many_arguments)]
+    pub fn price_barrier_option(
+        &self,
+        spot: U256,
+        strike: U256,
+        barrier: U256,
+        barrier_type: u8,
+        rate: U256,
+        vol: U256,
+        time: U256,
+        is_call: bool,
+        paths: u32,
+        steps: u32,
+        seed: FixedBytes<32>,
+    ) -> Result<(U256, U256), Vec<u8>> {
+        let market = options::Market::new(spot, strike, rate, vol, time)?;
+        let payoff = montecarlo::Payoff::barrier(is_call, barrier_type, barrier)?;
+        let (price, std_error) = montecarlo::price(&market, payoff, paths, steps, seed)?;
+        Ok((U256::from(price as u128), U256::from(std_error as u128)))
+    }
+
+    // Splits `deposit` across strategies to maximize expected yield while the
+    // deposit's average risk stays within `risk_budget`; see allocation.rs.
+    // APYs are 18-decimal fractions and risk scores share the budget's scale.
+    // Returns the amount per strategy and the blended APY of the deposit.
+    pub fn optimize_yield(
+        &self,
+        deposit: U256,
+        apys: Vec<U256>,
+        risks: Vec<U256>,
+        capacities: Vec<U256>,
+        risk_budget: U256,
+    ) -> Result<(Vec<U256>, U256), Vec<u8>> {
+        if apys.is_empty() || apys.len() > allocation::MAX_STRATEGIES {
+            return Err("Invalid number of strategies".into());
+        }
+        if risks.len() != apys.len() || capacities.len() != apys.len() {
+            return Err("Strategy arrays differ in length".into());
+        }
+        let deposit = allocation::parse_amount(deposit)?;
+        if deposit == 0 {
+            return Err("Empty deposit".into());
+        }
+        let risk_budget = allocation::parse_risk(risk_budget)?;
+
+        let mut strategies = Vec::with_capacity(apys.len());
+        for ((apy, risk), capacity) in apys.into_iter().zip(risks).zip(capacities) {
+            strategies.push(allocation::Strategy::new(apy, risk, capacity)?);
+        }
+
+        let amounts = allocation::optimize(&strategies, deposit, risk_budget);
+        let apy = allocation::blended_apy(&strategies, &amounts, deposit);
+        Ok((
+            amounts.into_iter().map(|amount| U256::from(amount as u128)).collect(),
+            U256::from(apy as u128),
+        ))
+    }
+
+    // Annualized realized volatility of the pool price (18 decimals) and the
+    // swap fee it sets
+    pub fn get_pool_volatility(&self, pool_id: FixedBytes<32>) -> Result<(U256, U256), Vec<u8>> {
+        let variance = self.poolVolatility.get(pool_id).to::<u128>() as i128;
+        let volatility = oracle::volatility(variance)?;
+        Ok((U256::from(volatility as u128), self.calculate_dynamic_fee(pool_id)))
+    }
+
+    // Retained price observations, oldest first, with their timestamps
+    pub fn get_pool_observations(&self, pool_id: FixedBytes<32>) -> (Vec<U256>, Vec<U256>) {
+        let count = self.poolObservationCount.get(pool_id).to::<u64>();
+        let first = count.saturating_sub(oracle::OBSERVATION_CAPACITY);
+        (first..count)
+            .map(|index| {
+                let slot = U256::from(index % oracle::OBSERVATION_CAPACITY);
+                (
+                    self.poolObservationPrice.getter(pool_id).get(slot),
+                    self.poolObservationTime.getter(pool_id).get(slot),
+                )
+            })
+            .unzip()
+    }
+
+    pub fn set_pool_manager(&mut self, pool_manager: Address) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+        self.poolManager.set(pool_manager);
+        Ok(())
+    }
+
+    pub fn get_pool_manager(&self) -> Address {
+        self.poolManager.get()
     }
 
     // Add Uniswap v4-style hook implementation
@@ -540,6 +1919,7 @@ impl ArbiProofSimulator {
     ) -> Result<U256, Vec<u8>> {
         // This demonstrates a hook that would be called from Solidity code
         // in a real Uniswap v4-style implementation
+        self.only_pool_manager()?;
         
         // Perform complex validation logic that would be expensive in Solidity
         if !self.validate_complex_swap_conditions(pool_id, amount_in) {
@@ -555,10 +1935,632 @@ impl ArbiProofSimulator {
         // Return the fee to be applied by the calling Solidity contract
         Ok(fee)
     }
+
+    // Folds the pool price the swap left behind, in any fixed unit, into the
+    // pool's volatility, and so into the next swap's fee
+    pub fn hook_after_swap(&mut self, pool_id: FixedBytes<32>, price: U256) -> Result<(), Vec<u8>> {
+        self.only_pool_manager()?;
+        self.record_price_observation(pool_id, price)
+    }
 }
 
 // Add the helper functions as implementation methods
 impl ArbiProofSimulator {
+    fn load_dispute(&self, dispute_id: FixedBytes<32>) -> Dispute {
+        Dispute {
+            challenger: self.disputeChallenger.get(dispute_id),
+            defender: self.disputeDefender.get(dispute_id),
+            status: self.disputeStatus.get(dispute_id).to::<u8>(),
+            current_round: self.disputeCurrentRound.get(dispute_id),
+            total_rounds: self.disputeTotalRounds.get(dispute_id),
+            timestamp: self.disputeTimestamp.get(dispute_id),
+            assertion_id: self.disputeAssertionId.get(dispute_id),
+            range_start: self.disputeRangeStart.get(dispute_id),
+            range_end: self.disputeRangeEnd.get(dispute_id),
+            start_hash: self.disputeStartHash.get(dispute_id),
+            challenger_end_hash: self.disputeChallengerEndHash.get(dispute_id),
+            defender_end_hash: self.disputeDefenderEndHash.get(dispute_id),
+            challenger_stake: self.disputeChallengerStake.get(dispute_id),
+            defender_stake: self.disputeDefenderStake.get(dispute_id),
+            bond_deadline: self.disputeBondDeadline.get(dispute_id),
+            clock_budget: self.disputeClockBudget.get(dispute_id),
+        }
+    }
+
+    fn load_challenge_round(&self, dispute_id: FixedBytes<32>, round: U256) -> ChallengeRound {
+        ChallengeRound {
+            round,
+            bisection_point: self.roundBisectionPoint.getter(dispute_id).get(round),
+            challenger_claim: self.roundChallengerClaim.getter(dispute_id).get(round),
+            defender_response: self.roundDefenderResponse.getter(dispute_id).get(round),
+            status: self.roundStatus.getter(dispute_id).get(round).to::<u8>(),
+        }
+    }
+
+    // The defender owes an answer to an open round; otherwise the challenger
+    // has to bisect, or prove the last step once a single one remains
+    fn turn_of(&self, dispute_id: FixedBytes<32>) -> Address {
+        let current_round = self.disputeCurrentRound.get(dispute_id);
+        if current_round > U256::ZERO
+            && self.roundStatus.getter(dispute_id).get(current_round) == U256::from(ROUND_PENDING)
+        {
+            return self.disputeDefender.get(dispute_id);
+        }
+        self.disputeChallenger.get(dispute_id)
+    }
+
+    fn opponent_of(&self, dispute_id: FixedBytes<32>, party: Address) -> Address {
+        let challenger = self.disputeChallenger.get(dispute_id);
+        if party == challenger {
+            self.disputeDefender.get(dispute_id)
+        } else {
+            challenger
+        }
+    }
+
+    fn stored_clock(&self, dispute_id: FixedBytes<32>, party: Address) -> U256 {
+        if party == self.disputeChallenger.get(dispute_id) {
+            self.disputeChallengerClock.get(dispute_id)
+        } else {
+            self.disputeDefenderClock.get(dispute_id)
+        }
+    }
+
+    fn elapsed_since_last_move(&self, dispute_id: FixedBytes<32>) -> U256 {
+        U256::from(block::timestamp()).saturating_sub(self.disputeLastMoveAt.get(dispute_id))
+    }
+
+    // A party's clock only runs while the dispute is in progress and it is
+    // their move
+    fn clock_remaining(&self, dispute_id: FixedBytes<32>, party: Address) -> U256 {
+        let clock = self.stored_clock(dispute_id, party);
+        if self.disputeStatus.get(dispute_id) != U256::from(STATUS_IN_PROGRESS)
+            || party != self.turn_of(dispute_id)
+        {
+            return clock;
+        }
+        clock.saturating_sub(self.elapsed_since_last_move(dispute_id))
+    }
+
+    fn clock_expired(&self, dispute_id: FixedBytes<32>) -> bool {
+        let turn = self.turn_of(dispute_id);
+        self.elapsed_since_last_move(dispute_id) > self.stored_clock(dispute_id, turn)
+    }
+
+    // Charges the time spent on this move to the mover's clock and hands the
+    // turn over
+    fn charge_clock(&mut self, dispute_id: FixedBytes<32>, mover: Address) -> Result<(), Vec<u8>> {
+        let elapsed = self.elapsed_since_last_move(dispute_id);
+        let clock = self.stored_clock(dispute_id, mover);
+        if elapsed > clock {
+            return Err("Clock expired".into());
+        }
+
+        if mover == self.disputeChallenger.get(dispute_id) {
+            self.disputeChallengerClock.insert(dispute_id, clock - elapsed);
+        } else {
+            self.disputeDefenderClock.insert(dispute_id, clock - elapsed);
+        }
+        self.disputeLastMoveAt.insert(dispute_id, U256::from(block::timestamp()));
+
+        Ok(())
+    }
+
+    // keccak256(parent_id ++ state_root ++ proposer)
+    fn derive_assertion_id(
+        parent_id: FixedBytes<32>,
+        state_root: FixedBytes<32>,
+        proposer: Address,
+    ) -> FixedBytes<32> {
+        let mut hasher = Keccak256::new();
+        hasher.update(parent_id.as_slice());
+        hasher.update(state_root.as_slice());
+        hasher.update(proposer.as_slice());
+        FixedBytes::<32>::from_slice(&hasher.finalize())
+    }
+
+    // keccak256(assertion_id ++ challenger ++ nonce)
+    fn derive_dispute_id(assertion_id: FixedBytes<32>, challenger: Address, nonce: U256) -> FixedBytes<32> {
+        let mut hasher = Keccak256::new();
+        hasher.update(assertion_id.as_slice());
+        hasher.update(challenger.as_slice());
+        hasher.update(nonce.to_be_bytes::<32>());
+        FixedBytes::<32>::from_slice(&hasher.finalize())
+    }
+
+    fn derive_vk_id(vk: &[U256]) -> FixedBytes<32> {
+        let mut hasher = Keccak256::new();
+        for word in vk {
+            hasher.update(word.to_be_bytes::<32>());
+        }
+        FixedBytes::<32>::from_slice(&hasher.finalize())
+    }
+
+    // Words of a registered verifying key, empty if it is unknown
+    fn load_verifying_key(&self, vk_id: FixedBytes<32>) -> Vec<U256> {
+        let len = self.vkLength.get(vk_id).as_limbs()[0];
+        let words = self.vkWords.getter(vk_id);
+        (0..len).map(|index| words.get(U256::from(index))).collect()
+    }
+
+    // Moves a dispute to `status`, keeping the per-status counts in step
+    fn set_status(&mut self, dispute_id: FixedBytes<32>, status: u64) {
+        // New disputes have no previous status to count down
+        if self.dispute_exists(dispute_id) {
+            let previous = self.disputeStatus.get(dispute_id);
+            let count = self.statusCount.get(previous);
+            self.statusCount.insert(previous, count - U256::from(1));
+        }
+
+        let status = U256::from(status);
+        self.disputeStatus.insert(dispute_id, status);
+        let count = self.statusCount.get(status);
+        self.statusCount.insert(status, count + U256::from(1));
+    }
+
+    // Positions [offset, offset + limit) of a list of `count` entries, clamped
+    // to the list and to MAX_PAGE_SIZE
+    fn page(count: U256, offset: U256, limit: U256) -> core::ops::Range<u64> {
+        let count = count.as_limbs()[0];
+        let start = offset.min(U256::from(count)).as_limbs()[0];
+        let limit = limit.min(U256::from(MAX_PAGE_SIZE)).as_limbs()[0];
+        start..(start + limit).min(count)
+    }
+
+    fn only_owner(&self) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.owner.get() {
+            return Err("Only owner".into());
+        }
+        Ok(())
+    }
+
+    fn only_pool_manager(&self) -> Result<(), Vec<u8>> {
+        if msg::sender() != self.poolManager.get() {
+            return Err("Only pool manager".into());
+        }
+        Ok(())
+    }
+
+    // Reads a game parameter, falling back to its default while unset
+    fn config_value(&self, parameter: u64) -> U256 {
+        let value = self.config.get(U256::from(parameter));
+        if value != U256::ZERO {
+            return value;
+        }
+
+        U256::from(match parameter {
+            CONFIG_STAKE => DEFAULT_STAKE,
+            CONFIG_CHALLENGE_PERIOD => DEFAULT_CHALLENGE_PERIOD,
+            CONFIG_BOND_WINDOW => DEFAULT_BOND_WINDOW,
+            CONFIG_MAX_ROUNDS => DEFAULT_MAX_ROUNDS,
+            CONFIG_CLOCK_BUDGET => DEFAULT_CLOCK_BUDGET,
+            CONFIG_ASSERTION_BOND => DEFAULT_ASSERTION_BOND,
+            _ => 0,
+        })
+    }
+
+    // Overridable parameters default to the configured value on both ends
+    fn config_bounds(&self, parameter: u64) -> (U256, U256) {
+        let min = self.configMin.get(U256::from(parameter));
+        if min == U256::ZERO {
+            let value = self.config_value(parameter);
+            return (value, value);
+        }
+        (min, self.configMax.get(U256::from(parameter)))
+    }
+
+    // A dispute's value of `parameter`: the configured one when no override
+    // is requested, else the override if it is within bounds
+    fn game_parameter(&self, parameter: u64, requested: U256) -> Result<U256, Vec<u8>> {
+        if requested == U256::ZERO {
+            return Ok(self.config_value(parameter));
+        }
+        let (min, max) = self.config_bounds(parameter);
+        if requested < min || requested > max {
+            return Err("Override out of bounds".into());
+        }
+        Ok(requested)
+    }
+
+    fn update_config(&mut self, parameter: u64, value: U256) -> Result<(), Vec<u8>> {
+        self.only_owner()?;
+        if value == U256::ZERO {
+            return Err("Invalid config value".into());
+        }
+
+        let old_value = self.config_value(parameter);
+        self.config.insert(U256::from(parameter), value);
+
+        evm::log(ConfigUpdated {
+            parameter: U256::from(parameter),
+            oldValue: old_value,
+            newValue: value,
+        });
+
+        Ok(())
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn create_dispute(
+        &mut self,
+        assertion_id: FixedBytes<32>,
+        trace_length: U256,
+        end_hash: FixedBytes<32>,
+        end_state: FixedBytes<32>,
+        trace_root: FixedBytes<32>,
+        stake: U256,
+        clock_budget: U256,
+        bond_window: U256,
+        mode: u64,
+    ) -> Result<FixedBytes<32>, Vec<u8>> {
+        let caller = msg::sender();
+        let value = msg::value();
+
+        // Every dispute is staked at exactly the configured amount, or an
+        // override within the owner's bounds, so a defender can always match it
+        if value != self.game_parameter(CONFIG_STAKE, stake)? {
+            return Err("Stake must equal the configured amount".into());
+        }
+
+        if trace_length == U256::ZERO {
+            return Err("Empty trace".into());
+        }
+
+        // Every bisection claim is checked against a commitment: the trace
+        // root, or in history mode the end root itself
+        if mode == MODE_STATE_HASH && trace_root == FixedBytes::<32>::default() {
+            return Err("Empty trace root".into());
+        }
+
+        // Challengers can pile onto a pending assertion until its challenge
+        // window closes, and all of them fight its proposer
+        if !self.assertion_exists(assertion_id) {
+            return Err("Assertion not found".into());
+        }
+        if self.assertionStatus.get(assertion_id) != U256::from(ASSERTION_PENDING) {
+            return Err("Assertion not pending".into());
+        }
+        if U256::from(block::timestamp()) > self.assertionDeadline.get(assertion_id) {
+            return Err("Assertion challenge window has passed".into());
+        }
+        let defender = self.assertionProposer.get(assertion_id);
+        if defender == caller {
+            return Err("Cannot dispute own assertion".into());
+        }
+
+        // The challenger has to end somewhere other than the assertion, which
+        // the defender is held to
+        if end_state == self.assertionStateRoot.get(assertion_id) {
+            return Err("End state matches assertion".into());
+        }
+
+        // Every halving costs a round: ceil(log2(trace_length)) rounds in total
+        let total_rounds = U256::from((trace_length - U256::from(1)).bit_len());
+        if total_rounds > self.config_value(CONFIG_MAX_ROUNDS) {
+            return Err("Trace too long".into());
+        }
+
+        let clock_budget = self.game_parameter(CONFIG_CLOCK_BUDGET, clock_budget)?;
+        let bond_window = self.game_parameter(CONFIG_BOND_WINDOW, bond_window)?;
+
+        // Each dispute consumes the challenger's next nonce, so ids never repeat
+        let nonce = self.disputeNonce.get(caller);
+        let dispute_id = Self::derive_dispute_id(assertion_id, caller, nonce);
+        if self.dispute_exists(dispute_id) {
+            return Err("Dispute already exists".into());
+        }
+        self.disputeNonce.insert(caller, nonce + U256::from(1));
+
+        // Store dispute fields individually; the status goes first, while the
+        // dispute does not exist yet
+        self.set_status(dispute_id, STATUS_PENDING);
+        self.disputeChallenger.insert(dispute_id, caller);
+        self.disputeDefender.insert(dispute_id, defender);
+        self.disputeCurrentRound.insert(dispute_id, U256::ZERO);
+        self.disputeTotalRounds.insert(dispute_id, total_rounds);
+        self.disputeTimestamp.insert(dispute_id, U256::from(block::timestamp()));
+        self.disputeClockBudget.insert(dispute_id, clock_budget);
+        self.disputeAssertionId.insert(dispute_id, assertion_id);
+        self.disputeOutcome.insert(dispute_id, U256::from(OUTCOME_NONE));
+        self.disputeReason.insert(dispute_id, U256::from(REASON_NONE));
+
+        // Both parties agree on the state before step 0, the one the parent
+        // assertion settled on, and disagree after the last step
+        let mut start_hash = self.parent_state_root(assertion_id);
+        if mode == MODE_HISTORY {
+            start_hash = history::root(&history::append_leaf(&[], start_hash)?)?;
+        }
+        self.disputeRangeStart.insert(dispute_id, U256::ZERO);
+        self.disputeRangeEnd.insert(dispute_id, trace_length);
+        self.disputeStartHash.insert(dispute_id, start_hash);
+        self.disputeChallengerEndHash.insert(dispute_id, end_hash);
+        self.disputeTraceRoot.insert(dispute_id, trace_root);
+        self.disputeMode.insert(dispute_id, U256::from(mode));
+
+        // Escrow the full stake until the dispute is settled
+        self.disputeChallengerStake.insert(dispute_id, value);
+
+        // The dispute only goes live once the defender matches the stake
+        let deadline = U256::from(block::timestamp()) + bond_window;
+        self.disputeBondDeadline.insert(dispute_id, deadline);
+
+        // Initialize rounds count
+        self.roundsCount.insert(dispute_id, U256::ZERO);
+
+        // Index the dispute for enumeration
+        let index = self.disputeCount.get();
+        self.disputeIds.insert(index, dispute_id);
+        self.disputeCount.set(index + U256::from(1));
+        self.challengerDisputes.setter(caller).insert(nonce, dispute_id);
+        let defender_index = self.defenderDisputeCount.get(defender);
+        self.defenderDisputes.setter(defender).insert(defender_index, dispute_id);
+        self.defenderDisputeCount.insert(defender, defender_index + U256::from(1));
+
+        // Track the dispute among the open ones on its assertion
+        let open_count = self.assertionOpenCount.get(assertion_id) + U256::from(1);
+        self.assertionOpenDisputes.setter(assertion_id).insert(open_count, dispute_id);
+        self.assertionOpenCount.insert(assertion_id, open_count);
+        self.disputeOpenIndex.insert(dispute_id, open_count);
+
+        evm::log(DisputeInitiated {
+            disputeId: dispute_id.0,
+            challenger: caller,
+            defender,
+        });
+
+        Ok(dispute_id)
+    }
+
+    // Marks the assertion invalid and pays its bond to `recipient`; the dispute
+    // id is zero when the assertion fell with its parent
+    fn invalidate_assertion(
+        &mut self,
+        assertion_id: FixedBytes<32>,
+        dispute_id: FixedBytes<32>,
+        recipient: Address,
+    ) -> Result<(), Vec<u8>> {
+        self.assertionStatus.insert(assertion_id, U256::from(ASSERTION_INVALID));
+
+        let bond = self.assertionBond.get(assertion_id);
+        self.assertionBond.insert(assertion_id, U256::ZERO);
+        if bond > U256::ZERO {
+            call::transfer_eth(recipient, bond)?;
+        }
+
+        evm::log(AssertionInvalidated {
+            assertionId: assertion_id.0,
+            disputeId: dispute_id.0,
+            challenger: recipient,
+        });
+
+        Ok(())
+    }
+
+    fn bond_defender(&mut self, dispute_id: FixedBytes<32>, end_hash: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        let status = self.disputeStatus.get(dispute_id);
+        if status != U256::from(STATUS_PENDING) {
+            return Err("Dispute not pending".into());
+        }
+
+        self.ensure_assertion_live(dispute_id)?;
+
+        let defender = self.disputeDefender.get(dispute_id);
+        if msg::sender() != defender {
+            return Err("Only defender can post bond".into());
+        }
+
+        let deadline = self.disputeBondDeadline.get(dispute_id);
+        if U256::from(block::timestamp()) > deadline {
+            return Err("Bond window has passed".into());
+        }
+
+        // The bond matches the stake the dispute was opened with, whatever the
+        // configured stake is by now
+        let value = msg::value();
+        if value != self.disputeChallengerStake.get(dispute_id) {
+            return Err("Bond must match challenger stake".into());
+        }
+
+        self.disputeDefenderStake.insert(dispute_id, value);
+        self.disputeDefenderEndHash.insert(dispute_id, end_hash);
+        self.set_status(dispute_id, STATUS_IN_PROGRESS);
+
+        // The game starts once both sides are at stake
+        let now = U256::from(block::timestamp());
+        self.disputeTimestamp.insert(dispute_id, now);
+
+        // Both clocks start full, and the challenger is first to move
+        let clock_budget = self.disputeClockBudget.get(dispute_id);
+        self.disputeChallengerClock.insert(dispute_id, clock_budget);
+        self.disputeDefenderClock.insert(dispute_id, clock_budget);
+        self.disputeLastMoveAt.insert(dispute_id, now);
+
+        evm::log(DefenderBondPosted {
+            disputeId: dispute_id.0,
+            defender,
+            amount: value,
+        });
+
+        Ok(())
+    }
+
+    // Checks that `trace_root` opens with `start_hash` at step 0 and closes
+    // with `end_hash` at step trace_length
+    fn verify_trace_ends(
+        trace_root: FixedBytes<32>,
+        trace_length: U256,
+        start_hash: FixedBytes<32>,
+        end_hash: FixedBytes<32>,
+        start_proof: &[FixedBytes<32>],
+        end_proof: &[FixedBytes<32>],
+    ) -> Result<(), Vec<u8>> {
+        if !merkle::verify(start_proof, trace_root, merkle::step_leaf(U256::ZERO, start_hash)) {
+            return Err("Start state not in trace root".into());
+        }
+        if !merkle::verify(end_proof, trace_root, merkle::step_leaf(trace_length, end_hash)) {
+            return Err("End state not in trace root".into());
+        }
+        Ok(())
+    }
+
+    // Checks that `end_root` commits to trace_length + 1 states, the last of
+    // which is `end_state`
+    fn verify_history_end(
+        end_root: FixedBytes<32>,
+        trace_length: U256,
+        end_state: FixedBytes<32>,
+        end_expansion: &[FixedBytes<32>],
+    ) -> Result<(), Vec<u8>> {
+        if U256::from(history::tree_size(end_expansion)) != trace_length {
+            return Err("End expansion does not match trace length".into());
+        }
+        if history::root(&history::append_leaf(end_expansion, end_state)?)? != end_root {
+            return Err("End state not at the end of the history".into());
+        }
+        Ok(())
+    }
+
+    // State the execution asserted by `assertion_id` starts from
+    fn parent_state_root(&self, assertion_id: FixedBytes<32>) -> FixedBytes<32> {
+        let parent_id = self.assertionParent.get(assertion_id);
+        if parent_id == FixedBytes::<32>::default() {
+            self.genesisStateRoot.get()
+        } else {
+            self.assertionStateRoot.get(parent_id)
+        }
+    }
+
+    fn assertion_invalidated(&self, dispute_id: FixedBytes<32>) -> bool {
+        let assertion_id = self.disputeAssertionId.get(dispute_id);
+        self.assertionStatus.get(assertion_id) == U256::from(ASSERTION_INVALID)
+    }
+
+    // Moves are pointless once another challenger has won against the assertion
+    fn ensure_assertion_live(&self, dispute_id: FixedBytes<32>) -> Result<(), Vec<u8>> {
+        if self.assertion_invalidated(dispute_id) {
+            return Err("Assertion already invalidated".into());
+        }
+        Ok(())
+    }
+
+    // Drops a dispute from its assertion's open list by moving the last entry
+    // into its slot
+    fn close_dispute(&mut self, dispute_id: FixedBytes<32>) {
+        let assertion_id = self.disputeAssertionId.get(dispute_id);
+        let slot = self.disputeOpenIndex.get(dispute_id);
+        let last = self.assertionOpenCount.get(assertion_id);
+
+        if slot != last {
+            let moved = self.assertionOpenDisputes.getter(assertion_id).get(last);
+            self.assertionOpenDisputes.setter(assertion_id).insert(slot, moved);
+            self.disputeOpenIndex.insert(moved, slot);
+        }
+        self.assertionOpenDisputes.setter(assertion_id).insert(last, FixedBytes::<32>::default());
+        self.assertionOpenCount.insert(assertion_id, last - U256::from(1));
+        self.disputeOpenIndex.insert(dispute_id, U256::ZERO);
+    }
+
+    // Marks the dispute resolved, records how it ended and pays the winner
+    fn finalize_dispute(
+        &mut self,
+        dispute_id: FixedBytes<32>,
+        winner: Address,
+        outcome: u8,
+        reason: u8,
+    ) -> Result<(), Vec<u8>> {
+        self.set_status(dispute_id, STATUS_RESOLVED);
+        self.disputeOutcome.insert(dispute_id, U256::from(outcome));
+        self.disputeReason.insert(dispute_id, U256::from(reason));
+        self.disputeWinner.insert(dispute_id, winner);
+        self.close_dispute(dispute_id);
+
+        // A cancelled dispute has no winner, the challenger just gets the stake back
+        let challenger = self.disputeChallenger.get(dispute_id);
+        let payee = if outcome == OUTCOME_CANCELLED { challenger } else { winner };
+
+        // A single challenger win is enough to bring the assertion down, and
+        // that challenger collects the proposer's bond
+        let assertion_id = self.disputeAssertionId.get(dispute_id);
+        if winner == challenger && self.assertionStatus.get(assertion_id) == U256::from(ASSERTION_PENDING) {
+            self.invalidate_assertion(assertion_id, dispute_id, challenger)?;
+        }
+
+        evm::log(DisputeResolved {
+            disputeId: dispute_id.0,
+            winner,
+            outcome,
+            reason,
+        });
+
+        self.settle_dispute(dispute_id, payee)
+    }
+
+    // Signer of a 65-byte r || s || v signature over `digest`, via ecrecover
+    fn recover_signer(&self, digest: FixedBytes<32>, signature: &[u8]) -> Result<Address, Vec<u8>> {
+        if signature.len() != 65 {
+            return Err("Invalid signature length".into());
+        }
+
+        let r = &signature[..32];
+        let s = &signature[32..64];
+        let v = match signature[64] {
+            v @ (27 | 28) => v,
+            v @ (0 | 1) => v + 27,
+            _ => return Err("Invalid signature".into()),
+        };
+
+        // Only low-s signatures, so a signature cannot be replayed in its other form
+        if U256::from_be_slice(s) > SECP256K1N_HALF {
+            return Err("Invalid signature".into());
+        }
+
+        let mut input = Vec::with_capacity(128);
+        input.extend_from_slice(digest.as_slice());
+        input.extend_from_slice(&U256::from(v).to_be_bytes::<32>());
+        input.extend_from_slice(r);
+        input.extend_from_slice(s);
+
+        let output = call::static_call(self, ECRECOVER, &input)?;
+        if output.len() != 32 {
+            return Err("Invalid signature".into());
+        }
+
+        let signer = Address::from_slice(&output[12..]);
+        if signer == Address::ZERO {
+            return Err("Invalid signature".into());
+        }
+        Ok(signer)
+    }
+
+    // Releases the escrowed pot of a dispute: the protocol fee goes to the fee
+    // recipient and the remainder to the winner
+    fn settle_dispute(&mut self, dispute_id: FixedBytes<32>, winner: Address) -> Result<(), Vec<u8>> {
+        let pot = self.disputeChallengerStake.get(dispute_id) + self.disputeDefenderStake.get(dispute_id);
+
+        // Clear the ledger before any ETH leaves the contract
+        self.disputeChallengerStake.insert(dispute_id, U256::ZERO);
+        self.disputeDefenderStake.insert(dispute_id, U256::ZERO);
+
+        let fee = pot * self.protocolFeeBps.get() / U256::from(BPS_DENOMINATOR);
+        let payout = pot - fee;
+
+        if fee > U256::ZERO {
+            call::transfer_eth(self.feeRecipient.get(), fee)?;
+        }
+        if payout > U256::ZERO {
+            call::transfer_eth(winner, payout)?;
+        }
+
+        evm::log(DisputeSettled {
+            disputeId: dispute_id.0,
+            winner,
+            payout,
+            fee,
+        });
+
+        Ok(())
+    }
+
     // Helper functions for the hook
     fn validate_complex_swap_conditions(&self, pool_id: FixedBytes<32>, amount: U256) -> bool {
         // Perform complex validation that would be expensive in Solidity
@@ -579,8 +2581,8 @@ impl ArbiProofSimulator {
                 // Hash-based validation (expensive in Solidity)
                 let mut hasher = Keccak256::new();
                 hasher.update(pool_id.as_slice());
-                hasher.update(&amount.to_be_bytes::<32>());
-                hasher.update(&factor.to_be_bytes::<32>());
+                hasher.update(amount.to_be_bytes::<32>());
+                hasher.update(factor.to_be_bytes::<32>());
                 let hash = hasher.finalize();
                 
                 // Check first byte
@@ -595,27 +2597,48 @@ impl ArbiProofSimulator {
     }
 
     fn calculate_dynamic_fee(&self, pool_id: FixedBytes<32>) -> U256 {
-        // Perform complex fee calculation
-        let base_fee = U256::from(300); // 0.3%
-        let liquidity = self.poolLiquidity.get(pool_id);
-        
-        if liquidity == U256::ZERO {
-            return base_fee;
-        }
-        
-        // Dynamic fee based on volatility simulation
-        let mut volatility = U256::ZERO;
-        for i in 0..10 {
-            let seed = U256::from(i);
-            let mut hasher = Keccak256::new();
-            hasher.update(pool_id.as_slice());
-            hasher.update(&seed.to_be_bytes::<32>());
-            let hash = FixedBytes::<32>::from_slice(&hasher.finalize());
-            volatility += U256::from_be_bytes::<32>(hash.as_slice().try_into().unwrap()) % U256::from(100);
+        // Higher realized volatility means a higher fee, see oracle.rs
+        let variance = self.poolVolatility.get(pool_id).to::<u128>() as i128;
+        let volatility = oracle::volatility(variance).unwrap_or(0);
+        U256::from(oracle::dynamic_fee(volatility))
+    }
+
+    // Appends the price to the pool's ring buffer and updates the volatility.
+    // A price observed in the same second as the latest one replaces it as
+    // that second's close, and the second's return is sampled again from the
+    // previous close and the variance from before it.
+    fn record_price_observation(&mut self, pool_id: FixedBytes<32>, price: U256) -> Result<(), Vec<u8>> {
+        let current = oracle::parse_price(price)?;
+        let now = block::timestamp();
+        let count = self.poolObservationCount.get(pool_id).to::<u64>();
+
+        if count > 0 {
+            let last = U256::from((count - 1) % oracle::OBSERVATION_CAPACITY);
+            let observed_at = self.poolObservationTime.getter(pool_id).get(last).to::<u64>();
+            if now <= observed_at {
+                self.poolObservationPrice.setter(pool_id).insert(last, price);
+                if count > 1 {
+                    let close = U256::from((count - 2) % oracle::OBSERVATION_CAPACITY);
+                    let previous = self.poolObservationPrice.getter(pool_id).get(close).to::<u128>() as i128;
+                    let closed_at = self.poolObservationTime.getter(pool_id).get(close).to::<u64>();
+                    let variance = self.poolVarianceBefore.get(pool_id).to::<u128>() as i128;
+                    let variance = oracle::update_variance(variance, previous, current, observed_at - closed_at)?;
+                    self.poolVolatility.insert(pool_id, U256::from(variance as u128));
+                }
+                return Ok(());
+            }
+            let previous = self.poolObservationPrice.getter(pool_id).get(last).to::<u128>() as i128;
+            let before = self.poolVolatility.get(pool_id);
+            let variance = oracle::update_variance(before.to::<u128>() as i128, previous, current, now - observed_at)?;
+            self.poolVarianceBefore.insert(pool_id, before);
+            self.poolVolatility.insert(pool_id, U256::from(variance as u128));
         }
-        
-        // Adjust fee based on volatility (higher volatility = higher fee)
-        base_fee + (volatility / U256::from(50))
+
+        let slot = U256::from(count % oracle::OBSERVATION_CAPACITY);
+        self.poolObservationPrice.setter(pool_id).insert(slot, price);
+        self.poolObservationTime.setter(pool_id).insert(slot, U256::from(now));
+        self.poolObservationCount.insert(pool_id, U256::from(count + 1));
+        Ok(())
     }
 
     fn record_swap_activity(&mut self, pool_id: FixedBytes<32>, user: Address, amount: U256, _token: Address) {
@@ -627,4 +2650,1468 @@ impl ArbiProofSimulator {
         let current_fees = self.swapFees.get(pool_id);
         self.swapFees.insert(pool_id, current_fees + amount / U256::from(1000)); // 0.1% fee
     }
-}
\ No newline at end of file
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloy_sol_types::{SolEvent, TopicList};
+    use serde_json::Value;
+    use testing::{signature_of, Chain};
+
+    // The frontend ABI is a JSON array literal inside a TypeScript module
+    const FRONTEND_ABI: &str = include_str!("../../../src/contracts/ArbiProofSimulator.ts");
+
+    fn frontend_events() -> Vec<Value> {
+        let start = FRONTEND_ABI.find('[').unwrap();
+        let end = FRONTEND_ABI.rfind(']').unwrap();
+        let abi: Value = serde_json::from_str(&FRONTEND_ABI[start..=end]).unwrap();
+
+        abi.as_array()
+            .unwrap()
+            .iter()
+            .filter(|item| item["type"] == "event")
+            .cloned()
+            .collect()
+    }
+
+    // (signature, topic0, topic count) of every event the contract emits
+    fn contract_events() -> Vec<(&'static str, FixedBytes<32>, usize)> {
+        macro_rules! event {
+            ($event:ty) => {
+                (
+                    <$event as SolEvent>::SIGNATURE,
+                    <$event as SolEvent>::SIGNATURE_HASH,
+                    <<$event as SolEvent>::TopicList as TopicList>::COUNT,
+                )
+            };
+        }
+
+        vec![
+            event!(DisputeInitiated),
+            event!(DefenderBondPosted),
+            event!(BisectionChallenge),
+            event!(DefenderResponse),
+            event!(OneStepProofVerified),
+            event!(DisputeResolved),
+            event!(DisputeSettled),
+            event!(ConfigUpdated),
+            event!(ConfigBoundsUpdated),
+            event!(ProtocolFeeUpdated),
+            event!(TimeoutClaimed),
+            event!(AssertionPosted),
+            event!(AssertionConfirmed),
+            event!(AssertionInvalidated),
+            event!(DisputeCancelled),
+            event!(MutualSettlement),
+            event!(VerifyingKeyRegistered),
+        ]
+    }
+
+    #[test]
+    fn event_topics_match_frontend_abi() {
+        let abi_events = frontend_events();
+        let contract_events = contract_events();
+        assert_eq!(abi_events.len(), contract_events.len());
+
+        for (signature, topic0, topic_count) in contract_events {
+            let name = &signature[..signature.find('(').unwrap()];
+            let event = abi_events
+                .iter()
+                .find(|event| event["name"] == name)
+                .unwrap_or_else(|| panic!("{name} missing from frontend ABI"));
+            let inputs = event["inputs"].as_array().unwrap();
+
+            let types: Vec<&str> = inputs.iter().map(|input| input["type"].as_str().unwrap()).collect();
+            let abi_signature = format!("{name}({})", types.join(","));
+            assert_eq!(signature, abi_signature);
+
+            let abi_topic0 = FixedBytes::<32>::from_slice(&Keccak256::digest(abi_signature.as_bytes()));
+            assert_eq!(topic0, abi_topic0, "{name} topic hash");
+
+            let indexed = inputs.iter().filter(|input| input["indexed"] == true).count();
+            assert_eq!(topic_count, indexed + 1, "{name} indexed inputs");
+        }
+    }
+
+    #[test]
+    fn trace_proofs_use_sorted_pair_hashing() {
+        let leaves: Vec<FixedBytes<32>> = (0u8..4)
+            .map(|step| merkle::step_leaf(U256::from(step), FixedBytes::from([step + 1; 32])))
+            .collect();
+        let left = merkle::hash_pair(leaves[0], leaves[1]);
+        let right = merkle::hash_pair(leaves[2], leaves[3]);
+        let root = merkle::hash_pair(left, right);
+
+        // Sorted pairs make the node hash independent of child order
+        assert_eq!(merkle::hash_pair(leaves[1], leaves[0]), left);
+        let mut sorted = [leaves[0].0, leaves[1].0];
+        sorted.sort();
+        assert_eq!(left, FixedBytes::<32>::from_slice(&Keccak256::digest(sorted.concat())));
+
+        assert!(merkle::verify(&[leaves[3], left], root, leaves[2]));
+        assert!(merkle::verify(&[leaves[0], right], root, leaves[1]));
+
+        // The step index is part of the leaf, so a state cannot be claimed at another step
+        let moved = merkle::step_leaf(U256::from(3), FixedBytes::from([3u8; 32]));
+        assert!(!merkle::verify(&[leaves[3], left], root, moved));
+    }
+
+    #[test]
+    fn history_prefix_proofs() {
+        let states: Vec<FixedBytes<32>> = (1u8..=13).map(|i| FixedBytes::from([i; 32])).collect();
+        let expansion_of = |leaves: &[FixedBytes<32>]| {
+            leaves
+                .iter()
+                .fold(Vec::new(), |expansion, state| history::append_leaf(&expansion, *state).unwrap())
+        };
+
+        // Complete subtrees, largest aligned first, that take n leaves to m
+        let prefix_proof = |n: usize, m: usize| {
+            let mut proof = Vec::new();
+            let mut size = n;
+            while size < m {
+                let level = (0..64)
+                    .rev()
+                    .find(|level| size.is_multiple_of(1 << level) && size + (1 << level) <= m)
+                    .unwrap();
+                let subtree = expansion_of(&states[size..size + (1 << level)]);
+                proof.push(*subtree.last().unwrap());
+                size += 1 << level;
+            }
+            proof
+        };
+
+        for m in 2..=states.len() {
+            let post_root = history::root(&expansion_of(&states[..m])).unwrap();
+            for n in 1..m {
+                let pre_expansion = expansion_of(&states[..n]);
+                let pre_root = history::root(&pre_expansion).unwrap();
+                let proof = prefix_proof(n, m);
+                let (pre_size, post_size) = (n as u64, m as u64);
+
+                assert_eq!(
+                    history::verify_prefix_proof(pre_root, pre_size, post_root, post_size, &pre_expansion, &proof),
+                    Ok(())
+                );
+
+                // A history that diverges after the prefix is rejected
+                let mut forged = proof.clone();
+                forged[0] = FixedBytes::from([0xff; 32]);
+                assert!(history::verify_prefix_proof(pre_root, pre_size, post_root, post_size, &pre_expansion, &forged)
+                    .is_err());
+            }
+        }
+    }
+
+    #[test]
+    fn groth16_benchmark_instance_is_on_the_curve() {
+        let p = groth16::FIELD_MODULUS;
+        let (vk, proof, inputs) = groth16::benchmark_instance();
+        assert_eq!(groth16::input_count(vk.len()), Some(inputs.len()));
+        assert_eq!(proof.len(), groth16::PROOF_WORDS);
+        assert_eq!(groth16::input_count(vk.len() - 1), None);
+
+        // y^2 = x^3 + 3 over the base field
+        let on_curve = |x: U256, y: U256| y.mul_mod(y, p) == x.mul_mod(x, p).mul_mod(x, p).add_mod(U256::from(3), p);
+        let g1_points = [&vk[0..2], &vk[14..16], &vk[16..18], &proof[0..2], &proof[6..8]];
+        for point in g1_points {
+            assert!(on_curve(point[0], point[1]));
+        }
+
+        let a = [proof[0], proof[1]];
+        let neg_a = groth16::negate(a);
+        assert!(on_curve(neg_a[0], neg_a[1]));
+        assert_eq!(neg_a[1].add_mod(a[1], p), U256::ZERO);
+        assert_eq!(groth16::negate([U256::ZERO, U256::ZERO]), [U256::ZERO, U256::ZERO]);
+    }
+
+    #[test]
+    fn goldilocks_field_arithmetic() {
+        let p = fri::MODULUS;
+        assert_eq!(fri::add(p - 1, 2), 1);
+        assert_eq!(fri::sub(1, 2), p - 1);
+        assert_eq!(fri::mul(p - 1, p - 1), 1);
+        assert_eq!(fri::mul(1 << 32, 1 << 32), (1 << 32) - 1);
+        assert_eq!(fri::mul(fri::inverse(123_456_789), 123_456_789), 1);
+
+        // Roots of unity have exactly the requested order
+        for log_size in [1, 4, 16, 32] {
+            let omega = fri::root_of_unity(log_size);
+            assert_eq!(fri::pow(omega, 1 << log_size), 1);
+            assert_eq!(fri::pow(omega, 1 << (log_size - 1)), p - 1);
+        }
+    }
+
+    #[test]
+    fn fri_accepts_low_degree_and_rejects_tampering() {
+        let (log_degree, log_blowup, queries, proof) = fri::benchmark_instance();
+        assert_eq!(fri::verify(log_degree, log_blowup, queries, &proof), Ok(true));
+
+        // f(x) = 1 + 2x + ... + 16x^15 at x = 1
+        let evaluations = fri::evaluate(&(1..=16).collect::<Vec<u64>>(), log_degree + log_blowup);
+        assert_eq!(evaluations[0], 136);
+
+        let mut tampered = proof.clone();
+        tampered.values[3] = fri::add(tampered.values[3], 1);
+        assert_eq!(fri::verify(log_degree, log_blowup, queries, &tampered), Ok(false));
+
+        let mut tampered = proof.clone();
+        tampered.final_value = fri::add(tampered.final_value, 1);
+        assert_eq!(fri::verify(log_degree, log_blowup, queries, &tampered), Ok(false));
+
+        let mut truncated = proof.clone();
+        truncated.paths.pop();
+        assert!(fri::verify(log_degree, log_blowup, queries, &truncated).is_err());
+
+        // A degree-31 polynomial does not pass as degree < 16, even from an
+        // honest prover
+        let coefficients: Vec<u64> = (1..=32).collect();
+        let evaluations = fri::evaluate(&coefficients, log_degree + log_blowup);
+        let proof = fri::prove(&evaluations, log_degree, queries).unwrap();
+        assert_eq!(fri::verify(log_degree, log_blowup, queries, &proof), Ok(false));
+
+        // ... but does as degree < 32
+        let proof = fri::prove(&evaluations, log_degree + 1, queries).unwrap();
+        assert_eq!(fri::verify(log_degree + 1, log_blowup - 1, queries, &proof), Ok(true));
+    }
+
+    #[test]
+    fn fixed_point_functions_match_reference() {
+        let close = |actual: i128, expected: i128, tolerance: i128| {
+            assert!((actual - expected).abs() <= tolerance, "{actual} != {expected}");
+        };
+
+        close(fixed::ln(3 * fixed::WAD).unwrap(), 1_098_612_288_668_109_691, 100);
+        close(fixed::ln(fixed::WAD / 1000).unwrap(), -6_907_755_278_982_137_052, 100);
+        assert_eq!(fixed::ln(fixed::WAD), Ok(0));
+        assert!(fixed::ln(0).is_err());
+
+        close(fixed::exp(2_500_000_000_000_000_000).unwrap(), 12_182_493_960_703_473_438, 1_000);
+        close(fixed::exp(-10 * fixed::WAD).unwrap(), 45_399_929_762_484, 100);
+        assert_eq!(fixed::exp(0), Ok(fixed::WAD));
+        assert!(fixed::exp(fixed::MAX_EXP_INPUT + 1).is_err());
+
+        assert_eq!(fixed::sqrt(2 * fixed::WAD), Ok(1_414_213_562_373_095_048));
+        assert_eq!(fixed::sqrt(4 * fixed::WAD), Ok(2 * fixed::WAD));
+
+        close(fixed::norm_cdf(1_500_000_000_000_000_000), 933_192_798_731_141_933, 10_000);
+        close(fixed::norm_cdf(300_000_000_000_000_000), 617_911_422_188_952_637, 10_000);
+        close(fixed::norm_cdf(-8 * fixed::WAD), 622, 10);
+        assert_eq!(fixed::norm_cdf(0), fixed::WAD / 2);
+        assert_eq!(fixed::norm_cdf(10 * fixed::WAD), fixed::WAD);
+    }
+
+    #[test]
+    fn black_scholes_matches_reference() {
+        // (spot, strike, rate, vol, time, call, put), prices from an exact
+        // 60-digit evaluation
+        let cases: [(i128, i128, i128, i128, i128, i128, i128); 8] = [
+            (100_000_000_000_000_000_000, 100_000_000_000_000_000_000, 50_000_000_000_000_000, 200_000_000_000_000_000, 1_000_000_000_000_000_000, 10_450_583_572_185_566_781, 5_573_526_022_256_967_690),
+            (100_000_000_000_000_000_000, 110_000_000_000_000_000_000, 30_000_000_000_000_000, 250_000_000_000_000_000, 500_000_000_000_000_000, 3_898_551_183_185_060_247, 12_260_864_539_521_953_009),
+            (50_000_000_000_000_000_000, 40_000_000_000_000_000_000, 0, 600_000_000_000_000_000, 2_000_000_000_000_000_000, 20_264_383_141_011_435_509, 10_264_383_141_011_435_509),
+            (2_000_000_000_000_000_000_000, 2_500_000_000_000_000_000_000, 10_000_000_000_000_000, 800_000_000_000_000_000, 100_000_000_000_000_000, 58_812_736_905_013_296_269, 556_313_986_488_450_775_439),
+            (1_000_000_000_000_000_000, 1_000_000_000_000_000_000, 50_000_000_000_000_000, 10_000_000_000_000_000, 10_000_000_000_000_000, 697_622_122_667_457, 197_747_101_836_727),
+            (3_000_000_000_000_000_000_000, 3_000_000_000_000_000_000_000, 1_000_000_000_000_000_000, 10_000_000_000_000_000_000, 100_000_000_000_000_000_000, 3_000_000_000_000_000_000_000, 0),
+            (1_000_000_000_000_000_000_000_000_000, 1_200_000_000_000_000_000_000_000_000, 40_000_000_000_000_000, 350_000_000_000_000_000, 30_000_000_000_000_000_000, 810_923_834_315_558_914_866_263_810, 172_356_888_610_201_430_840_236_939),
+            (400_000_000_000_000, 300_000_000_000_000, 20_000_000_000_000_000, 1_500_000_000_000_000_000, 250_000_000_000_000_000, 160_107_602_347_689, 58_611_346_105_493),
+        ];
+
+        for (spot, strike, rate, vol, time, call, put) in cases {
+            let u = |value: i128| U256::from(value as u128);
+            let market = options::Market::new(u(spot), u(strike), u(rate), u(vol), u(time)).unwrap();
+            let tolerance = spot.max(strike) / 100_000_000_000_000 + 2;
+            assert!((market.price(true).unwrap() - call).abs() <= tolerance, "call {spot} {strike}");
+            assert!((market.price(false).unwrap() - put).abs() <= tolerance, "put {spot} {strike}");
+        }
+
+        let wad = U256::from(fixed::WAD as u128);
+        assert!(options::Market::new(wad, wad, U256::ZERO, U256::ZERO, wad).is_err());
+        assert!(options::Market::new(wad, wad, U256::from(2) * wad, wad, wad).is_err());
+    }
+
+    #[test]
+    fn greeks_match_reference() {
+        // (spot, strike, rate, vol, time), then call and put Greeks from an
+        // exact 60-digit evaluation
+        let cases: [([i128; 5], [i128; 5], [i128; 5]); 2] = [
+            (
+                [100_000_000_000_000_000_000, 100_000_000_000_000_000_000, 50_000_000_000_000_000, 200_000_000_000_000_000, 1_000_000_000_000_000_000],
+                [636_830_651_175_619_071, 18_762_017_345_846_893, 37_524_034_691_693_787_837, -6_414_027_546_438_195_800, 53_232_481_545_376_340_340],
+                [-363_169_348_824_380_928, 18_762_017_345_846_893, 37_524_034_691_693_787_837, -1_657_880_423_934_625_755, -41_890_460_904_695_060_568],
+            ),
+            (
+                [2_000_000_000_000_000_000_000, 2_500_000_000_000_000_000_000, 10_000_000_000_000_000, 800_000_000_000_000_000, 100_000_000_000_000_000],
+                [226_143_300_967_768_658, 594_456_821_809_494, 190_226_182_979_038_214_180, -764_839_470_566_458_096_920, 39_347_386_503_052_401_978],
+                [-773_856_699_032_231_341, 594_456_821_809_494, 190_226_182_979_038_214_180, -739_864_458_070_623_722_128, -210_402_738_455_291_345_938],
+            ),
+        ];
+
+        for ([spot, strike, rate, vol, time], call, put) in cases {
+            let u = |value: i128| U256::from(value as u128);
+            let market = options::Market::new(u(spot), u(strike), u(rate), u(vol), u(time)).unwrap();
+            for (is_call, expected) in [(true, call), (false, put)] {
+                let greeks = market.greeks(is_call).unwrap();
+                let actual = [greeks.delta, greeks.gamma, greeks.vega, greeks.theta, greeks.rho];
+                for (actual, expected) in actual.into_iter().zip(expected) {
+                    let tolerance = expected.abs() / 1_000_000_000_000 + 10_000;
+                    assert!((actual - expected).abs() <= tolerance, "{actual} != {expected}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn implied_volatility_inverts_the_pricer() {
+        let wad = fixed::WAD;
+        let u = |value: i128| U256::from(value as u128);
+        let market = options::Market::without_vol(u(100 * wad), u(120 * wad), u(wad / 20), u(wad / 2)).unwrap();
+
+        for vol in [wad / 100, wad / 5, 3 * wad / 4, 3 * wad] {
+            for is_call in [true, false] {
+                let priced = options::Market { vol, ..market };
+                let price = priced.price(is_call).unwrap();
+                let implied = market.implied_vol(price, is_call).unwrap();
+
+                // Recovered to the vol that reproduces the price
+                let repriced = options::Market { vol: implied, ..market }.price(is_call).unwrap();
+                assert!((repriced - price).abs() <= 120 * wad / 10_000_000_000_000 + 2);
+                if vol >= wad / 5 {
+                    assert!((implied - vol).abs() < wad / 1_000_000_000, "{implied} != {vol}");
+                }
+            }
+        }
+
+        // Above the spot no volatility prices a call
+        assert!(market.implied_vol(101 * wad, true).is_err());
+    }
+
+    #[test]
+    fn yield_allocation_spends_the_risk_budget_optimally() {
+        let wad = fixed::WAD;
+        let strategy = |apy_bps: i128, risk: i128, capacity: i128| allocation::Strategy {
+            apy: apy_bps * wad / 10_000,
+            risk: risk * wad,
+            capacity: capacity * wad,
+        };
+        let strategies = [strategy(300, 1, 100), strategy(800, 5, 100), strategy(1800, 9, 100)];
+
+        // A loose budget puts everything in the best strategy
+        let amounts = allocation::optimize(&strategies, 100 * wad, 9 * wad);
+        assert_eq!(amounts, vec![0, 0, 100 * wad]);
+
+        // At an average risk of 4 the LP optimum mixes the safest and the
+        // riskiest strategy: a + c = 100, a + 9c = 400
+        let amounts = allocation::optimize(&strategies, 100 * wad, 4 * wad);
+        let expected = [62_500_000_000_000_000_000, 0, 37_500_000_000_000_000_000];
+        for (amount, expected) in amounts.iter().zip(expected) {
+            assert!((amount - expected).abs() <= 1_000, "{amount} != {expected}");
+        }
+        let apy = allocation::blended_apy(&strategies, &amounts, 100 * wad);
+        assert!((apy - 86_250_000_000_000_000).abs() <= 1_000);
+
+        // A budget below every risk score leaves part of the deposit idle
+        let strategies = [strategy(500, 2, 1_000), strategy(900, 6, 1_000)];
+        let amounts = allocation::optimize(&strategies, 100 * wad, wad);
+        assert!((amounts[0] - 50 * wad).abs() <= 1_000 && amounts[1] == 0);
+
+        // Capacity caps each strategy and the rest flows down the ranking
+        let strategies = [strategy(400, 1, 30), strategy(700, 2, 50), strategy(200, 0, 1_000)];
+        let amounts = allocation::optimize(&strategies, 100 * wad, 10 * wad);
+        assert_eq!(amounts, vec![30 * wad, 50 * wad, 20 * wad]);
+    }
+
+    #[test]
+    fn polar_normals_have_unit_variance() {
+        let mut sampler = montecarlo::NormalSampler::new(FixedBytes::from([1u8; 32]));
+        let samples: Vec<i128> = (0..20_000).map(|_| sampler.sample()).collect();
+        let n = samples.len() as i128;
+        let mean = samples.iter().sum::<i128>() / n;
+        let variance = samples.iter().map(|z| fixed::mul(z - mean, z - mean)).sum::<i128>() / (n - 1);
+
+        // Three standard errors of the sample mean and variance
+        assert!(mean.abs() < 3 * fixed::WAD / 141, "mean {mean}");
+        assert!((variance - fixed::WAD).abs() < 3 * fixed::WAD / 100, "variance {variance}");
+
+        // Same seed, same stream
+        let mut replay = montecarlo::NormalSampler::new(FixedBytes::from([1u8; 32]));
+        assert!(samples[..10].iter().all(|z| *z == replay.sample()));
+    }
+
+    #[test]
+    fn monte_carlo_path_options() {
+        let wad = fixed::WAD;
+        let u = |value: i128| U256::from(value as u128);
+        let market = options::Market::new(u(100 * wad), u(100 * wad), u(wad / 20), u(wad / 5), u(wad)).unwrap();
+        let seed = FixedBytes::from([42u8; 32]);
+        let european = market.price(true).unwrap();
+
+        // A single monitoring date makes the Asian call a European one
+        let asian = montecarlo::Payoff::Asian { is_call: true };
+        let (price, std_error) = montecarlo::price(&market, asian, 4_000, 1, seed).unwrap();
+        assert!((price - european).abs() < 3 * std_error, "{price} +- {std_error} vs {european}");
+
+        // Averaging damps volatility, so the monthly Asian is cheaper
+        let (asian_price, _) = montecarlo::price(&market, asian, 1_000, 12, seed).unwrap();
+        assert!(asian_price < european);
+
+        // In and out barriers over the same paths add up to the vanilla, up
+        // to the rounding of the running mean
+        let barrier = |kind| montecarlo::Payoff::barrier(true, kind, u(120 * wad)).unwrap();
+        let (out, _) = montecarlo::price(&market, barrier(montecarlo::BARRIER_UP_AND_OUT), 1_000, 12, seed).unwrap();
+        let (knock_in, _) = montecarlo::price(&market, barrier(montecarlo::BARRIER_UP_AND_IN), 1_000, 12, seed).unwrap();
+        let never = montecarlo::Payoff::barrier(true, montecarlo::BARRIER_UP_AND_OUT, u(options::MAX_PRICE)).unwrap();
+        let (vanilla, _) = montecarlo::price(&market, never, 1_000, 12, seed).unwrap();
+        assert!(out > 0 && out < knock_in);
+        assert!((out + knock_in - vanilla).abs() < wad / 1_000_000_000, "{out} + {knock_in} vs {vanilla}");
+
+        // A barrier already breached at spot knocks out every path
+        let breached = montecarlo::Payoff::barrier(true, montecarlo::BARRIER_DOWN_AND_OUT, u(100 * wad)).unwrap();
+        assert_eq!(montecarlo::price(&market, breached, 100, 12, seed).unwrap(), (0, 0));
+
+        assert!(montecarlo::price(&market, asian, 1, 12, seed).is_err());
+        assert!(montecarlo::Payoff::barrier(true, 4, u(wad)).is_err());
+    }
+
+    #[test]
+    fn realized_volatility_drives_the_swap_fee() {
+        let wad = fixed::WAD;
+
+        // A flat price decays the estimate toward zero
+        let mut variance = wad;
+        for _ in 0..100 {
+            variance = oracle::update_variance(variance, 2_000 * wad, 2_000 * wad, 12).unwrap();
+        }
+        assert!(variance < wad / 100);
+
+        // Alternating daily 1% moves converge on the realized volatility
+        // |ln 1.01| * sqrt(365) = 19.01%
+        let (low, high) = (2_000 * wad, 2_020 * wad);
+        let mut variance = 0;
+        for step in 0..400 {
+            let (previous, price) = if step % 2 == 0 { (low, high) } else { (high, low) };
+            variance = oracle::update_variance(variance, previous, price, 86_400).unwrap();
+        }
+        let volatility = oracle::volatility(variance).unwrap();
+        assert!((volatility - 190_104_000_000_000_000).abs() < wad / 10_000, "{volatility}");
+
+        // The same move within a block is far more violent, and a repeat in
+        // the same second is ignored
+        let daily = oracle::update_variance(0, low, high, 86_400).unwrap();
+        assert!(oracle::update_variance(0, low, high, 12).unwrap() > 1_000 * daily);
+        assert_eq!(oracle::update_variance(variance, low, high, 0).unwrap(), variance);
+
+        // 0.3% flat, plus 0.1% per 10 points of volatility, capped at 5%
+        assert_eq!(oracle::dynamic_fee(0), 300);
+        assert_eq!(oracle::dynamic_fee(wad / 5), 500);
+        assert_eq!(oracle::dynamic_fee(volatility), 490);
+        assert_eq!(oracle::dynamic_fee(100 * wad), oracle::MAX_SWAP_FEE);
+
+        assert!(oracle::parse_price(U256::ZERO).is_err());
+        assert!(oracle::parse_price(U256::MAX).is_err());
+    }
+
+    #[test]
+    fn swaps_within_one_second_all_move_the_fee() {
+        let mut chain = deployed();
+        let pool = FixedBytes::repeat_byte(5);
+        let price = |basis_points: i128| U256::from((2_000 * fixed::WAD / 10_000 * (10_000 + basis_points)) as u128);
+        let fee = |chain: &Chain| chain.contract.get_pool_volatility(pool).unwrap().1;
+        chain.call(testing::DEPLOYER, 0, |c| c.set_pool_manager(OTHER)).unwrap();
+        assert_eq!(
+            chain.call(PROPOSER, 0, |c| c.hook_after_swap(pool, price(0))),
+            Err("Only pool manager".into())
+        );
+
+        chain.call(OTHER, 0, |c| c.hook_after_swap(pool, price(0))).unwrap();
+        chain.warp(12);
+        chain.call(OTHER, 0, |c| c.hook_after_swap(pool, price(0))).unwrap();
+        let flat = fee(&chain);
+
+        // A second swap in the block moves the close further from the last one
+        chain.warp(12);
+        chain.call(OTHER, 0, |c| c.hook_after_swap(pool, price(1))).unwrap();
+        let first = fee(&chain);
+        chain.call(OTHER, 0, |c| c.hook_after_swap(pool, price(2))).unwrap();
+        let second = fee(&chain);
+        assert!(flat < first && first < second, "{flat} {first} {second}");
+
+        // Coming back within the block leaves no trace
+        chain.call(OTHER, 0, |c| c.hook_after_swap(pool, price(0))).unwrap();
+        assert_eq!(fee(&chain), flat);
+        assert_eq!(chain.contract.get_pool_observations(pool).0.len(), 3);
+    }
+
+    fn instruction(opcode: u8, dst: u8, a: u8, b: u8, imm: u32) -> Vec<u8> {
+        let mut bytes = vec![opcode, dst, a, b];
+        bytes.extend_from_slice(&imm.to_be_bytes());
+        bytes
+    }
+
+    fn hash_node(left: FixedBytes<32>, right: FixedBytes<32>) -> FixedBytes<32> {
+        FixedBytes::from_slice(&Keccak256::digest([left.0, right.0].concat()))
+    }
+
+    // r0 = 5, r1 = r0 + r0, r2 = r1 * r1, halt
+    fn program() -> Vec<Vec<u8>> {
+        vec![
+            instruction(vm::OP_LOADI, 0, 0, 0, 5),
+            instruction(vm::OP_ADD, 1, 0, 0, 0),
+            instruction(vm::OP_MUL, 2, 1, 1, 0),
+            instruction(vm::OP_HALT, 0, 0, 0, 0),
+        ]
+    }
+
+    fn code_root() -> FixedBytes<32> {
+        let leaves: Vec<_> = program().iter().map(|code| vm::code_leaf(code)).collect();
+        hash_node(hash_node(leaves[0], leaves[1]), hash_node(leaves[2], leaves[3]))
+    }
+
+    fn code_proof(pc: usize) -> Vec<FixedBytes<32>> {
+        let leaves: Vec<_> = program().iter().map(|code| vm::code_leaf(code)).collect();
+        let other_pair = (pc / 2) ^ 1;
+        vec![
+            leaves[pc ^ 1],
+            hash_node(leaves[2 * other_pair], leaves[2 * other_pair + 1]),
+        ]
+    }
+
+    // Both parties' view of a run of the program, with a state-mode trace tree
+    struct Trace {
+        states: Vec<vm::MachineState>,
+        hashes: Vec<FixedBytes<32>>,
+        root: FixedBytes<32>,
+        proofs: Vec<Vec<FixedBytes<32>>>,
+    }
+
+    const TRACE_LENGTH: u64 = 4;
+
+    // A faulty run gets r1 wrong at step 2 and carries the error on
+    fn trace(faulty: bool) -> Trace {
+        let mut state = vm::MachineState {
+            code_root: code_root(),
+            ..Default::default()
+        };
+        let mut states = vec![state];
+        for step in 1..=TRACE_LENGTH {
+            let code = &program()[state.pc as usize];
+            state = vm::step(&state, &vm::Instruction::decode(code).unwrap());
+            if faulty && step == 2 {
+                state.registers[1] += U256::from(1);
+            }
+            states.push(state);
+        }
+        let hashes: Vec<_> = states.iter().map(|state| state.hash()).collect();
+
+        // Unpaired nodes move up a level unchanged
+        let mut levels = vec![hashes
+            .iter()
+            .enumerate()
+            .map(|(step, hash)| merkle::step_leaf(U256::from(step), *hash))
+            .collect::<Vec<_>>()];
+        while levels.last().unwrap().len() > 1 {
+            let level = levels.last().unwrap();
+            let next = level
+                .chunks(2)
+                .map(|pair| if pair.len() == 2 { merkle::hash_pair(pair[0], pair[1]) } else { pair[0] })
+                .collect();
+            levels.push(next);
+        }
+        let proofs = (0..hashes.len())
+            .map(|step| {
+                let mut index = step;
+                let mut proof = Vec::new();
+                for level in &levels[..levels.len() - 1] {
+                    if let Some(sibling) = level.get(index ^ 1) {
+                        proof.push(*sibling);
+                    }
+                    index /= 2;
+                }
+                proof
+            })
+            .collect();
+
+        Trace {
+            states,
+            hashes,
+            root: levels.last().unwrap()[0],
+            proofs,
+        }
+    }
+
+    const PROPOSER: Address = Address::with_last_byte(0x11);
+    const CHALLENGER: Address = Address::with_last_byte(0x22);
+    const OTHER: Address = Address::with_last_byte(0x33);
+    const FEE_RECIPIENT: Address = Address::with_last_byte(0x44);
+
+    fn deployed() -> Chain {
+        let mut chain = Chain::new();
+        let genesis = trace(false).hashes[0];
+        chain.call(testing::DEPLOYER, 0, |c| c.constructor(genesis)).unwrap();
+        chain
+    }
+
+    // Posts the end state of an honest or faulty run on top of genesis
+    fn assert_run(chain: &mut Chain, faulty: bool) -> FixedBytes<32> {
+        let state_root = trace(faulty).hashes[TRACE_LENGTH as usize];
+        chain
+            .call(PROPOSER, DEFAULT_ASSERTION_BOND, |c| c.post_assertion(FixedBytes::default(), state_root))
+            .unwrap()
+    }
+
+    fn open_dispute(chain: &mut Chain, assertion_id: FixedBytes<32>, challenger: Address, run: &Trace) -> FixedBytes<32> {
+        let end = TRACE_LENGTH as usize;
+        chain
+            .call(challenger, DEFAULT_STAKE, |c| {
+                c.initiate_dispute(
+                    assertion_id,
+                    U256::from(TRACE_LENGTH),
+                    run.hashes[end],
+                    run.root,
+                    run.proofs[0].clone(),
+                    run.proofs[end].clone(),
+                )
+            })
+            .unwrap()
+    }
+
+    fn bond_defender(chain: &mut Chain, dispute_id: FixedBytes<32>, run: &Trace) -> Result<(), String> {
+        let end = TRACE_LENGTH as usize;
+        chain.call(PROPOSER, DEFAULT_STAKE, |c| {
+            c.post_defender_bond(dispute_id, run.root, run.proofs[0].clone(), run.proofs[end].clone())
+        })
+    }
+
+    // Bisects down to a single step, each party answering from its own run,
+    // and returns the agreed step
+    fn bisect(chain: &mut Chain, dispute_id: FixedBytes<32>, challenger: &Trace, defender: &Trace) -> usize {
+        let mut round = 0;
+        loop {
+            let (start, end) = chain.contract.get_dispute_range(dispute_id).unwrap();
+            if end - start == U256::from(1) {
+                return start.to::<usize>();
+            }
+            let mid = (start + (end - start) / U256::from(2)).to::<usize>();
+            round += 1;
+            chain
+                .call(CHALLENGER, 0, |c| {
+                    c.submit_bisection_challenge(
+                        dispute_id,
+                        U256::from(mid),
+                        challenger.hashes[mid],
+                        challenger.proofs[mid].clone(),
+                        vec![],
+                    )
+                })
+                .unwrap();
+            chain.warp(60);
+            chain
+                .call(PROPOSER, 0, |c| {
+                    c.submit_defender_response(
+                        dispute_id,
+                        U256::from(round),
+                        defender.hashes[mid],
+                        defender.proofs[mid].clone(),
+                        vec![],
+                    )
+                })
+                .unwrap();
+            chain.warp(60);
+        }
+    }
+
+    fn prove_step(chain: &mut Chain, dispute_id: FixedBytes<32>, run: &Trace, step: usize) -> Result<Address, String> {
+        let pre_state = run.states[step];
+        let pc = pre_state.pc as usize;
+        chain.call(OTHER, 0, |c| {
+            c.submit_one_step_proof(
+                dispute_id,
+                pre_state.encode().into(),
+                program()[pc].clone().into(),
+                code_proof(pc),
+                vec![],
+            )
+        })
+    }
+
+    #[test]
+    fn vm_executes_every_opcode() {
+        let state = vm::MachineState {
+            registers: [U256::from(12), U256::from(10), U256::ZERO, U256::MAX],
+            ..Default::default()
+        };
+        let run = |opcode: u8, dst: u8, a: u8, b: u8| {
+            vm::step(&state, &vm::Instruction::decode(&instruction(opcode, dst, a, b, 7)).unwrap())
+        };
+
+        assert_eq!(run(vm::OP_LOADI, 2, 0, 0).registers[2], U256::from(7));
+        assert_eq!(run(vm::OP_ADD, 2, 0, 1).registers[2], U256::from(22));
+        assert_eq!(run(vm::OP_SUB, 2, 1, 0).registers[2], U256::MAX - U256::from(1));
+        assert_eq!(run(vm::OP_MUL, 2, 0, 1).registers[2], U256::from(120));
+        assert_eq!(run(vm::OP_AND, 2, 0, 1).registers[2], U256::from(8));
+        assert_eq!(run(vm::OP_OR, 2, 0, 1).registers[2], U256::from(14));
+        assert_eq!(run(vm::OP_XOR, 2, 0, 1).registers[2], U256::from(6));
+        assert_eq!(run(vm::OP_ADD, 2, 3, 0).registers[2], U256::from(11), "wrapping add");
+
+        // Only the two low bits pick a register
+        assert_eq!(run(vm::OP_ADD, 6, 4, 5).registers[2], U256::from(22));
+
+        // Arithmetic moves to the next instruction, HALT and unknown opcodes stay put
+        assert_eq!(run(vm::OP_NOP, 0, 0, 0), vm::MachineState { pc: 1, ..state });
+        assert_eq!(run(vm::OP_HALT, 0, 0, 0), state);
+        assert_eq!(run(0xff, 0, 0, 0), state);
+        assert_eq!(run(vm::OP_JUMP, 0, 0, 0).pc, 7);
+
+        let decoded = vm::MachineState::decode(&state.encode()).unwrap();
+        assert_eq!(decoded, state);
+        assert!(vm::MachineState::decode(&[0u8; vm::STATE_SIZE - 1]).is_err());
+        assert!(vm::Instruction::decode(&[0u8; vm::INSTRUCTION_SIZE + 1]).is_err());
+    }
+
+    #[test]
+    fn vm_jumpi_branches_on_a_nonzero_register() {
+        let mut state = vm::MachineState {
+            pc: 3,
+            ..Default::default()
+        };
+        let jumpi = vm::Instruction::decode(&instruction(vm::OP_JUMPI, 0, 1, 0, 9)).unwrap();
+
+        assert_eq!(vm::step(&state, &jumpi).pc, 4);
+        state.registers[1] = U256::from(1);
+        assert_eq!(vm::step(&state, &jumpi).pc, 9);
+    }
+
+    #[test]
+    fn one_step_checks_the_code_root_and_pc_range() {
+        let run = trace(false);
+        for step in 0..TRACE_LENGTH as usize {
+            let pc = run.states[step].pc as usize;
+            let post = vm::execute_one_step(&run.states[step].encode(), &program()[pc], &code_proof(pc));
+            assert_eq!(post, Ok(run.hashes[step + 1]));
+        }
+
+        // Another instruction, or the right one at another pc, is not in the program
+        let pre_state = run.states[1].encode();
+        assert_eq!(
+            vm::execute_one_step(&pre_state, &program()[2], &code_proof(1)),
+            Err("Instruction not in program")
+        );
+        assert_eq!(
+            vm::execute_one_step(&pre_state, &program()[1], &code_proof(2)),
+            Err("Instruction not in program")
+        );
+
+        // A two-level path only covers pcs 0..4, anything past it halts
+        let mut outside = run.states[1];
+        outside.pc = 4;
+        assert_eq!(
+            vm::execute_one_step(&outside.encode(), &program()[0], &code_proof(0)),
+            Ok(outside.hash())
+        );
+        assert_eq!(
+            vm::execute_one_step(&outside.encode(), &program()[1], &code_proof(0)),
+            Err("Instruction not in program")
+        );
+        assert_eq!(
+            vm::execute_one_step(&pre_state[1..], &program()[1], &code_proof(1)),
+            Err("Malformed machine state")
+        );
+    }
+
+    #[test]
+    fn vm_halts_past_the_end_of_the_program() {
+        // r0 = 7, jump to 9 in a four-instruction program
+        let code = [
+            instruction(vm::OP_LOADI, 0, 0, 0, 7),
+            instruction(vm::OP_JUMP, 0, 0, 0, 9),
+            instruction(vm::OP_NOP, 0, 0, 0, 0),
+            instruction(vm::OP_HALT, 0, 0, 0, 0),
+        ];
+        let leaves: Vec<_> = code.iter().map(|code| vm::code_leaf(code)).collect();
+        let mut state = vm::MachineState {
+            code_root: hash_node(hash_node(leaves[0], leaves[1]), hash_node(leaves[2], leaves[3])),
+            ..Default::default()
+        };
+        for code in &code[..2] {
+            state = vm::step(&state, &vm::Instruction::decode(code).unwrap());
+        }
+        assert_eq!(state.pc, 9);
+
+        // pc 9 wraps onto the jump at 1, which only shows the code size
+        let proof = vec![leaves[0], hash_node(leaves[2], leaves[3])];
+        assert_eq!(
+            ArbiProofSimulator::compute_one_step(state.encode().into(), code[1].clone().into(), proof.clone()),
+            Ok(state.hash())
+        );
+        assert_eq!(
+            ArbiProofSimulator::compute_one_step(state.encode().into(), code[2].clone().into(), proof),
+            Err("Instruction not in program".as_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn deployer_addresses_parse() {
+        assert_eq!(parse_address(None), Address::ZERO);
+        assert_eq!(
+            parse_address(Some("0x00000000000000000000000000000000000000De")),
+            Address::with_last_byte(0xde)
+        );
+        assert_eq!(
+            parse_address(Some("1111111111111111111111111111111111111111")),
+            Address::repeat_byte(0x11)
+        );
+    }
+
+    #[test]
+    fn only_the_deployer_initializes_once() {
+        let mut chain = Chain::new();
+        let genesis = trace(false).hashes[0];
+
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.constructor(genesis)),
+            Err("Only deployer can initialize".into())
+        );
+        assert_eq!(
+            chain.call(testing::DEPLOYER, 0, |c| c.constructor(FixedBytes::default())),
+            Err("Empty genesis state root".into())
+        );
+        chain.call(testing::DEPLOYER, 0, |c| c.constructor(genesis)).unwrap();
+        assert_eq!(chain.contract.get_genesis_state_root(), genesis);
+        assert_eq!(
+            chain.call(testing::DEPLOYER, 0, |c| c.constructor(genesis)),
+            Err("Already initialized".into())
+        );
+    }
+
+    #[test]
+    fn assertions_confirm_in_order_after_their_window() {
+        let mut chain = deployed();
+        let state_root = trace(false).hashes[TRACE_LENGTH as usize];
+
+        assert_eq!(
+            chain.call(PROPOSER, DEFAULT_ASSERTION_BOND - 1, |c| c.post_assertion(FixedBytes::default(), state_root)),
+            Err("Insufficient assertion bond".into())
+        );
+        assert_eq!(
+            chain.call(PROPOSER, DEFAULT_ASSERTION_BOND, |c| c.post_assertion(FixedBytes::default(), FixedBytes::default())),
+            Err("Empty state root".into())
+        );
+        let parent = assert_run(&mut chain, false);
+        let child = chain
+            .call(OTHER, DEFAULT_ASSERTION_BOND, |c| c.post_assertion(parent, FixedBytes::repeat_byte(7)))
+            .unwrap();
+        assert_eq!(chain.contract.get_assertion(child).unwrap().1, parent);
+
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.confirm_assertion(parent)),
+            Err("Assertion still in challenge window".into())
+        );
+        chain.warp(DEFAULT_CHALLENGE_PERIOD + 1);
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.confirm_assertion(child)),
+            Err("Parent assertion not confirmed".into())
+        );
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.reject_assertion(child)),
+            Err("Parent assertion not invalidated".into())
+        );
+
+        chain.call(OTHER, 0, |c| c.confirm_assertion(parent)).unwrap();
+        chain.call(PROPOSER, 0, |c| c.confirm_assertion(child)).unwrap();
+        assert_eq!(chain.received(PROPOSER), U256::from(DEFAULT_ASSERTION_BOND));
+        assert_eq!(chain.received(OTHER), U256::from(DEFAULT_ASSERTION_BOND));
+        assert_eq!(chain.contract.get_assertion(parent).unwrap().5, ASSERTION_CONFIRMED as u8);
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.confirm_assertion(parent)),
+            Err("Assertion not pending".into())
+        );
+
+        // Assertions on top of one that falls are rejected, their bond going
+        // to the owner
+        let faulty_parent = assert_run(&mut chain, true);
+        let orphan = chain
+            .call(OTHER, DEFAULT_ASSERTION_BOND, |c| c.post_assertion(faulty_parent, FixedBytes::repeat_byte(9)))
+            .unwrap();
+        let dispute_id = open_dispute(&mut chain, faulty_parent, CHALLENGER, &trace(false));
+        chain.warp(DEFAULT_BOND_WINDOW + 1);
+        chain.call(OTHER, 0, |c| c.resolve_dispute(dispute_id)).unwrap();
+        chain.call(OTHER, 0, |c| c.reject_assertion(orphan)).unwrap();
+        assert_eq!(chain.received(testing::DEPLOYER), U256::from(DEFAULT_ASSERTION_BOND));
+        assert_eq!(chain.contract.get_assertion(orphan).unwrap().5, ASSERTION_INVALID as u8);
+    }
+
+    #[test]
+    fn disputes_start_from_the_parent_root_and_end_off_the_assertion() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let end = TRACE_LENGTH as usize;
+        let initiate = |chain: &mut Chain, value: u64, run: &Trace, start_proof: Vec<FixedBytes<32>>| {
+            chain.call(CHALLENGER, value, |c| {
+                c.initiate_dispute(
+                    assertion_id,
+                    U256::from(TRACE_LENGTH),
+                    run.hashes[end],
+                    run.root,
+                    start_proof,
+                    run.proofs[end].clone(),
+                )
+            })
+        };
+
+        assert_eq!(
+            initiate(&mut chain, DEFAULT_STAKE + 1, &faulty, faulty.proofs[0].clone()),
+            Err("Stake must equal the configured amount".into())
+        );
+        assert_eq!(
+            initiate(&mut chain, DEFAULT_STAKE, &honest, honest.proofs[0].clone()),
+            Err("End state matches assertion".into())
+        );
+        assert_eq!(
+            initiate(&mut chain, DEFAULT_STAKE, &faulty, faulty.proofs[1].clone()),
+            Err("Start state not in trace root".into())
+        );
+        assert_eq!(
+            chain.call(PROPOSER, DEFAULT_STAKE, |c| {
+                c.initiate_dispute(
+                    assertion_id,
+                    U256::from(TRACE_LENGTH),
+                    faulty.hashes[end],
+                    faulty.root,
+                    faulty.proofs[0].clone(),
+                    faulty.proofs[end].clone(),
+                )
+            }),
+            Err("Cannot dispute own assertion".into())
+        );
+
+        let dispute_id = initiate(&mut chain, DEFAULT_STAKE, &faulty, faulty.proofs[0].clone()).unwrap();
+        let dispute = chain.contract.load_dispute(dispute_id);
+        assert_eq!(dispute.start_hash, honest.hashes[0]);
+        assert_eq!(dispute.challenger_end_hash, faulty.hashes[end]);
+        assert_eq!(dispute.challenger_stake, U256::from(DEFAULT_STAKE));
+        assert_eq!((dispute.range_start, dispute.range_end), (U256::ZERO, U256::from(TRACE_LENGTH)));
+        assert_eq!(dispute.total_rounds, U256::from(2));
+        assert_eq!(chain.emitted(DisputeInitiated::SIGNATURE_HASH), 1);
+
+        chain.warp(DEFAULT_CHALLENGE_PERIOD + 1);
+        assert_eq!(
+            initiate(&mut chain, DEFAULT_STAKE, &faulty, faulty.proofs[0].clone()),
+            Err("Assertion challenge window has passed".into())
+        );
+    }
+
+    #[test]
+    fn dispute_ids_use_the_challenger_nonce() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let faulty = trace(true);
+
+        let expected = chain.contract.compute_dispute_id(assertion_id, CHALLENGER);
+        let first = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        assert_eq!(first, expected);
+        let second = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        assert_ne!(first, second);
+
+        assert_eq!(chain.contract.get_challenger_disputes(CHALLENGER, U256::ZERO, U256::from(10)), vec![first, second]);
+        assert_eq!(chain.contract.get_defender_disputes(PROPOSER, U256::ZERO, U256::from(10)), vec![first, second]);
+        assert_eq!(chain.contract.get_open_disputes(assertion_id), vec![first, second]);
+        assert_eq!(chain.contract.get_status_counts(), (U256::from(2), U256::ZERO, U256::ZERO));
+    }
+
+    #[test]
+    fn defender_bond_has_to_match_in_time_with_its_own_trace() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        let end = TRACE_LENGTH as usize;
+
+        assert_eq!(
+            chain.call(PROPOSER, DEFAULT_STAKE - 1, |c| {
+                c.post_defender_bond(dispute_id, honest.root, honest.proofs[0].clone(), honest.proofs[end].clone())
+            }),
+            Err("Bond must match challenger stake".into())
+        );
+        assert_eq!(
+            chain.call(OTHER, DEFAULT_STAKE, |c| {
+                c.post_defender_bond(dispute_id, honest.root, honest.proofs[0].clone(), honest.proofs[end].clone())
+            }),
+            Err("Only defender can post bond".into())
+        );
+        // The defender's trace has to end in the assertion's state root
+        assert_eq!(bond_defender(&mut chain, dispute_id, &faulty), Err("End state not in trace root".into()));
+
+        chain.warp(DEFAULT_BOND_WINDOW + 1);
+        assert_eq!(bond_defender(&mut chain, dispute_id, &honest), Err("Bond window has passed".into()));
+
+        // A second dispute, bonded in time, goes live with both clocks full
+        let live = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, live, &honest).unwrap();
+        let dispute = chain.contract.load_dispute(live);
+        assert_eq!(dispute.status, STATUS_IN_PROGRESS as u8);
+        assert_eq!(dispute.defender_end_hash, honest.hashes[end]);
+        assert_eq!(dispute.defender_stake, U256::from(DEFAULT_STAKE));
+        let clock = U256::from(DEFAULT_CLOCK_BUDGET);
+        assert_eq!(chain.contract.get_dispute_clocks(live).unwrap(), (clock, clock, CHALLENGER));
+        assert_eq!(bond_defender(&mut chain, live, &honest), Err("Dispute not pending".into()));
+    }
+
+    #[test]
+    fn unbonded_disputes_go_to_the_challenger() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &trace(true));
+
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.resolve_dispute(dispute_id)),
+            Err("Defender can still post bond".into())
+        );
+        chain.warp(DEFAULT_BOND_WINDOW + 1);
+        chain.call(OTHER, 0, |c| c.resolve_dispute(dispute_id)).unwrap();
+
+        // The challenger gets its stake back and the proposer's bond
+        assert_eq!(
+            chain.received(CHALLENGER),
+            U256::from(DEFAULT_STAKE) + U256::from(DEFAULT_ASSERTION_BOND)
+        );
+        assert_eq!(chain.contract.get_dispute_stakes(dispute_id).unwrap(), (U256::ZERO, U256::ZERO));
+        assert_eq!(chain.contract.get_assertion(assertion_id).unwrap().5, ASSERTION_INVALID as u8);
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.resolve_dispute(dispute_id)),
+            Err("Dispute already resolved".into())
+        );
+
+    }
+
+    #[test]
+    fn defender_responses_answer_the_open_round_from_their_own_trace() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, dispute_id, &honest).unwrap();
+
+        let bisection = |chain: &mut Chain| {
+            chain.call(CHALLENGER, 0, |c| {
+                c.submit_bisection_challenge(dispute_id, U256::from(2), faulty.hashes[2], faulty.proofs[2].clone(), vec![])
+            })
+        };
+        let respond = |chain: &mut Chain, from: Address, round: u64, hash: FixedBytes<32>, proof_step: usize| {
+            chain.call(from, 0, |c| {
+                c.submit_defender_response(dispute_id, U256::from(round), hash, honest.proofs[proof_step].clone(), vec![])
+            })
+        };
+        assert_eq!(respond(&mut chain, PROPOSER, 0, honest.hashes[2], 2), Err("Not the current round".into()));
+
+        bisection(&mut chain).unwrap();
+        assert_eq!(bisection(&mut chain), Err("Awaiting defender response".into()));
+        assert_eq!(respond(&mut chain, CHALLENGER, 1, honest.hashes[2], 2), Err("Only defender can respond".into()));
+        assert_eq!(respond(&mut chain, PROPOSER, 2, honest.hashes[2], 2), Err("Not the current round".into()));
+        assert_eq!(respond(&mut chain, PROPOSER, 1, FixedBytes::default(), 2), Err("Empty response".into()));
+        assert_eq!(respond(&mut chain, PROPOSER, 1, honest.hashes[2], 1), Err("Response not in trace root".into()));
+
+        respond(&mut chain, PROPOSER, 1, honest.hashes[2], 2).unwrap();
+        assert_eq!(respond(&mut chain, PROPOSER, 1, honest.hashes[2], 2), Err("Round already answered".into()));
+        assert_eq!(chain.emitted(DefenderResponse::SIGNATURE_HASH), 1);
+    }
+
+    #[test]
+    fn bisection_halves_the_range_down_to_a_single_step() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+
+        let bisection = |chain: &mut Chain, from: Address, point: usize, proof_step: usize| {
+            chain.call(from, 0, |c| {
+                c.submit_bisection_challenge(
+                    dispute_id,
+                    U256::from(point),
+                    faulty.hashes[point],
+                    faulty.proofs[proof_step].clone(),
+                    vec![],
+                )
+            })
+        };
+        let respond = |chain: &mut Chain, round: u64, step: usize| {
+            chain.call(PROPOSER, 0, |c| {
+                c.submit_defender_response(
+                    dispute_id,
+                    U256::from(round),
+                    honest.hashes[step],
+                    honest.proofs[step].clone(),
+                    vec![],
+                )
+            })
+        };
+        assert_eq!(bisection(&mut chain, CHALLENGER, 2, 2), Err("Dispute not in progress".into()));
+        bond_defender(&mut chain, dispute_id, &honest).unwrap();
+        assert_eq!(
+            chain.contract.get_dispute_range(dispute_id).unwrap(),
+            (U256::ZERO, U256::from(TRACE_LENGTH))
+        );
+
+        assert_eq!(bisection(&mut chain, PROPOSER, 2, 2), Err("Only challenger can submit bisection".into()));
+        assert_eq!(bisection(&mut chain, CHALLENGER, 1, 1), Err("Bisection point is not the midpoint".into()));
+        assert_eq!(bisection(&mut chain, CHALLENGER, 2, 3), Err("Claim not in trace root".into()));
+
+        // Disagreeing keeps the lower half, agreeing then moves to its top
+        bisection(&mut chain, CHALLENGER, 2, 2).unwrap();
+        respond(&mut chain, 1, 2).unwrap();
+        assert_eq!(chain.contract.get_dispute_range(dispute_id).unwrap(), (U256::ZERO, U256::from(2)));
+        bisection(&mut chain, CHALLENGER, 1, 1).unwrap();
+        respond(&mut chain, 2, 1).unwrap();
+        assert_eq!(chain.contract.get_dispute_range(dispute_id).unwrap(), (U256::from(1), U256::from(2)));
+        assert_eq!(chain.contract.get_dispute_claims(dispute_id).unwrap().0, honest.hashes[1]);
+        assert_eq!(bisection(&mut chain, CHALLENGER, 1, 1), Err("Too many rounds".into()));
+    }
+
+    #[test]
+    fn one_step_proof_upholds_an_honest_assertion() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, dispute_id, &honest).unwrap();
+
+        assert_eq!(
+            prove_step(&mut chain, dispute_id, &honest, 0),
+            Err("Dispute not narrowed to a single step".into())
+        );
+        assert_eq!(bisect(&mut chain, dispute_id, &faulty, &honest), 1);
+
+        // Only the agreed pre-state can be replayed
+        assert_eq!(prove_step(&mut chain, dispute_id, &honest, 0), Err("Pre-state does not match agreed state".into()));
+        assert_eq!(prove_step(&mut chain, dispute_id, &honest, 1), Ok(PROPOSER));
+
+        assert_eq!(chain.received(PROPOSER), U256::from(2 * DEFAULT_STAKE));
+        assert_eq!(chain.emitted(OneStepProofVerified::SIGNATURE_HASH), 1);
+        assert_eq!(chain.contract.get_assertion(assertion_id).unwrap().5, ASSERTION_PENDING as u8);
+        assert_eq!(chain.contract.get_assertion(assertion_id).unwrap().6, U256::ZERO);
+    }
+
+    #[test]
+    fn challenge_rounds_are_stored_per_dispute_and_round() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        let other_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, dispute_id, &honest).unwrap();
+        bisect(&mut chain, dispute_id, &faulty, &honest);
+
+        let rounds = chain.contract.get_challenge_rounds(dispute_id).unwrap();
+        assert_eq!(
+            rounds,
+            vec![
+                (U256::from(1), U256::from(2), faulty.hashes[2], honest.hashes[2], ROUND_COMPLETED as u8),
+                (U256::from(2), U256::from(1), faulty.hashes[1], honest.hashes[1], ROUND_COMPLETED as u8),
+            ]
+        );
+        assert_eq!(chain.contract.get_challenge_rounds_count(dispute_id).unwrap(), U256::from(2));
+        assert_eq!(chain.contract.get_challenge_round(dispute_id, U256::from(2)).unwrap(), rounds[1]);
+        for round in [0, 3] {
+            assert_eq!(
+                chain.contract.get_challenge_round(dispute_id, U256::from(round)),
+                Err("Round not found".as_bytes().to_vec())
+            );
+        }
+
+        // Rounds of one dispute never show up under another
+        assert!(chain.contract.get_challenge_rounds(other_id).unwrap().is_empty());
+        assert_eq!(
+            chain.contract.get_challenge_rounds(FixedBytes::repeat_byte(1)),
+            Err("Dispute not found".as_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn dispute_views_return_typed_tuples() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let faulty = trace(true);
+        assert_eq!(
+            chain.contract.get_dispute(FixedBytes::repeat_byte(1)).err(),
+            Some("Dispute not found".as_bytes().to_vec())
+        );
+
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        let dispute = chain.contract.get_dispute(dispute_id).unwrap();
+        assert_eq!((dispute.0, dispute.1, dispute.2), (CHALLENGER, PROPOSER, STATUS_PENDING as u8));
+        assert_eq!(dispute.5, U256::from(chain.timestamp()));
+        assert_eq!(dispute.6, assertion_id);
+        assert_eq!((dispute.7, dispute.8), (U256::ZERO, U256::from(TRACE_LENGTH)));
+        assert_eq!(dispute.10, faulty.hashes[TRACE_LENGTH as usize]);
+        assert_eq!(dispute.12, U256::from(DEFAULT_STAKE));
+    }
+
+    #[test]
+    fn outcomes_are_recorded_when_a_dispute_resolves() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, dispute_id, &honest).unwrap();
+        assert_eq!(
+            chain.contract.get_dispute_outcome(dispute_id).unwrap(),
+            (OUTCOME_NONE, REASON_NONE, Address::ZERO)
+        );
+
+        bisect(&mut chain, dispute_id, &faulty, &honest);
+        prove_step(&mut chain, dispute_id, &honest, 1).unwrap();
+        assert_eq!(
+            chain.contract.get_dispute_outcome(dispute_id).unwrap(),
+            (OUTCOME_DEFENDER_WON, REASON_ONE_STEP_PROOF, PROPOSER)
+        );
+        assert_eq!(chain.emitted(DisputeResolved::SIGNATURE_HASH), 1);
+    }
+
+    #[test]
+    fn one_step_proof_brings_down_a_false_assertion_for_every_challenger() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, true);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &honest);
+        let piled_on = open_dispute(&mut chain, assertion_id, OTHER, &honest);
+        bond_defender(&mut chain, dispute_id, &faulty).unwrap();
+        bond_defender(&mut chain, piled_on, &faulty).unwrap();
+
+        let step = bisect(&mut chain, dispute_id, &honest, &faulty);
+        assert_eq!(step, 1);
+        assert_eq!(prove_step(&mut chain, dispute_id, &faulty, step), Ok(CHALLENGER));
+
+        assert_eq!(
+            chain.received(CHALLENGER),
+            U256::from(2 * DEFAULT_STAKE) + U256::from(DEFAULT_ASSERTION_BOND)
+        );
+        assert_eq!(chain.contract.get_assertion(assertion_id).unwrap().5, ASSERTION_INVALID as u8);
+
+        // The other dispute on the same assertion is over as well
+        assert_eq!(
+            chain.call(OTHER, 0, |c| {
+                c.submit_bisection_challenge(piled_on, U256::from(2), honest.hashes[2], honest.proofs[2].clone(), vec![])
+            }),
+            Err("Assertion already invalidated".into())
+        );
+        chain.call(PROPOSER, 0, |c| c.resolve_dispute(piled_on)).unwrap();
+        assert_eq!(
+            chain.contract.get_dispute_outcome(piled_on).unwrap(),
+            (OUTCOME_CHALLENGER_WON, REASON_ASSERTION_INVALIDATED, OTHER)
+        );
+        assert_eq!(chain.received(OTHER), U256::from(2 * DEFAULT_STAKE));
+        assert!(chain.contract.get_open_disputes(assertion_id).is_empty());
+    }
+
+    #[test]
+    fn clocks_forfeit_the_party_that_stalls() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let honest = trace(false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, dispute_id, &honest).unwrap();
+
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.claim_timeout(dispute_id)),
+            Err("Clock has not run out".into())
+        );
+
+        // The challenger spends an hour on its move, the defender then stalls
+        chain.warp(3600);
+        chain
+            .call(CHALLENGER, 0, |c| {
+                c.submit_bisection_challenge(dispute_id, U256::from(2), faulty.hashes[2], faulty.proofs[2].clone(), vec![])
+            })
+            .unwrap();
+        let budget = U256::from(DEFAULT_CLOCK_BUDGET);
+        assert_eq!(
+            chain.contract.get_dispute_clocks(dispute_id).unwrap(),
+            (budget - U256::from(3600), budget, PROPOSER)
+        );
+
+        chain.warp(DEFAULT_CLOCK_BUDGET + 1);
+        assert_eq!(
+            chain.call(PROPOSER, 0, |c| {
+                c.submit_defender_response(dispute_id, U256::from(1), honest.hashes[2], honest.proofs[2].clone(), vec![])
+            }),
+            Err("Clock expired".into())
+        );
+        chain.call(OTHER, 0, |c| c.resolve_dispute(dispute_id)).unwrap();
+        assert_eq!(
+            chain.contract.get_dispute_outcome(dispute_id).unwrap(),
+            (OUTCOME_TIMEOUT, REASON_CLOCK_EXPIRED, CHALLENGER)
+        );
+        assert_eq!(chain.emitted(TimeoutClaimed::SIGNATURE_HASH), 1);
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.claim_timeout(dispute_id)),
+            Err("Dispute not in progress".into())
+        );
+    }
+
+    #[test]
+    fn owner_sets_the_config_and_override_bounds() {
+        let mut chain = deployed();
+        let owner = testing::DEPLOYER;
+        let stake = U256::from(2 * DEFAULT_STAKE);
+
+        assert_eq!(chain.call(OTHER, 0, |c| c.set_stake(stake)), Err("Only owner".into()));
+        assert!(chain.call(owner, 0, |c| c.set_stake(U256::ZERO)).is_err());
+        chain.call(owner, 0, |c| c.set_stake(stake)).unwrap();
+        assert_eq!(chain.contract.get_config().0, stake);
+
+        let clock = CONFIG_CLOCK_BUDGET as u8;
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.set_config_bounds(clock, U256::from(60), U256::from(600))),
+            Err("Only owner".into())
+        );
+        assert_eq!(
+            chain.call(owner, 0, |c| c.set_config_bounds(CONFIG_MAX_ROUNDS as u8, U256::from(1), U256::from(2))),
+            Err("Parameter cannot be overridden".into())
+        );
+        assert_eq!(
+            chain.call(owner, 0, |c| c.set_config_bounds(clock, U256::from(600), U256::from(60))),
+            Err("Invalid config bounds".into())
+        );
+        // Unset bounds admit only the configured value
+        assert_eq!(
+            chain.contract.get_config_bounds(clock),
+            (U256::from(DEFAULT_CLOCK_BUDGET), U256::from(DEFAULT_CLOCK_BUDGET))
+        );
+        chain.call(owner, 0, |c| c.set_config_bounds(clock, U256::from(60), U256::from(600))).unwrap();
+
+        let assertion_id = assert_run(&mut chain, false);
+        let faulty = trace(true);
+        let end = TRACE_LENGTH as usize;
+        let initiate = |chain: &mut Chain, value: u64, clock_budget: u64| {
+            chain.call(CHALLENGER, value, |c| {
+                c.initiate_dispute_with_config(
+                    assertion_id,
+                    U256::from(TRACE_LENGTH),
+                    faulty.hashes[end],
+                    faulty.root,
+                    faulty.proofs[0].clone(),
+                    faulty.proofs[end].clone(),
+                    U256::from(clock_budget),
+                    U256::ZERO,
+                )
+            })
+        };
+
+        assert_eq!(initiate(&mut chain, 2 * DEFAULT_STAKE, 601), Err("Override out of bounds".into()));
+        // The stake has no bounds set, so only the configured one is accepted
+        assert_eq!(initiate(&mut chain, DEFAULT_STAKE, 300), Err("Override out of bounds".into()));
+        let dispute_id = initiate(&mut chain, 2 * DEFAULT_STAKE, 300).unwrap();
+        assert_eq!(
+            chain.contract.get_dispute_config(dispute_id).unwrap(),
+            (
+                U256::from(2),
+                U256::from(300),
+                U256::from(chain.timestamp() + DEFAULT_BOND_WINDOW)
+            )
+        );
+    }
+
+    #[test]
+    fn challengers_can_cancel_until_the_defender_bonds() {
+        let mut chain = deployed();
+        chain
+            .call(testing::DEPLOYER, 0, |c| c.set_protocol_fee(U256::from(100), FEE_RECIPIENT))
+            .unwrap();
+        let assertion_id = assert_run(&mut chain, false);
+        let faulty = trace(true);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+
+        assert_eq!(
+            chain.call(OTHER, 0, |c| c.cancel_dispute(dispute_id)),
+            Err("Only challenger can cancel".into())
+        );
+        chain.call(CHALLENGER, 0, |c| c.cancel_dispute(dispute_id)).unwrap();
+
+        // 1% protocol fee on the way out; the assertion stands
+        let fee = U256::from(DEFAULT_STAKE / 100);
+        assert_eq!(chain.received(CHALLENGER), U256::from(DEFAULT_STAKE) - fee);
+        assert_eq!(chain.received(FEE_RECIPIENT), fee);
+        assert_eq!(
+            chain.contract.get_dispute_outcome(dispute_id).unwrap(),
+            (OUTCOME_CANCELLED, REASON_CANCELLED_BY_CHALLENGER, Address::ZERO)
+        );
+        assert_eq!(chain.contract.get_assertion(assertion_id).unwrap().5, ASSERTION_PENDING as u8);
+
+        let bonded = open_dispute(&mut chain, assertion_id, CHALLENGER, &faulty);
+        bond_defender(&mut chain, bonded, &trace(false)).unwrap();
+        assert_eq!(
+            chain.call(CHALLENGER, 0, |c| c.cancel_dispute(bonded)),
+            Err("Dispute not pending".into())
+        );
+    }
+
+    #[test]
+    fn mutual_settlement_needs_both_signatures() {
+        let mut chain = deployed();
+        let assertion_id = assert_run(&mut chain, false);
+        let dispute_id = open_dispute(&mut chain, assertion_id, CHALLENGER, &trace(true));
+        bond_defender(&mut chain, dispute_id, &trace(false)).unwrap();
+        let deadline = U256::from(chain.timestamp() + 600);
+        let settle = |chain: &mut Chain, winner: Address, deadline: U256, challenger: Address, defender: Address| {
+            chain.call(OTHER, 0, |c| {
+                c.settle_mutually(
+                    dispute_id,
+                    winner,
+                    deadline,
+                    signature_of(challenger).into(),
+                    signature_of(defender).into(),
+                )
+            })
+        };
+
+        assert_eq!(
+            settle(&mut chain, PROPOSER, deadline, OTHER, PROPOSER),
+            Err("Invalid challenger signature".into())
+        );
+        assert_eq!(
+            settle(&mut chain, PROPOSER, deadline, CHALLENGER, OTHER),
+            Err("Invalid defender signature".into())
+        );
+        assert_eq!(
+            settle(&mut chain, OTHER, deadline, CHALLENGER, PROPOSER),
+            Err("Winner must be a party".into())
+        );
+        let expired = U256::from(chain.timestamp() - 1);
+        assert_eq!(
+            settle(&mut chain, PROPOSER, expired, CHALLENGER, PROPOSER),
+            Err("Settlement expired".into())
+        );
+
+        settle(&mut chain, PROPOSER, deadline, CHALLENGER, PROPOSER).unwrap();
+        assert_eq!(chain.received(PROPOSER), U256::from(2 * DEFAULT_STAKE));
+        assert_eq!(
+            chain.contract.get_dispute_outcome(dispute_id).unwrap(),
+            (OUTCOME_DEFENDER_WON, REASON_MUTUAL_SETTLEMENT, PROPOSER)
+        );
+        assert_eq!(
+            settle(&mut chain, PROPOSER, deadline, CHALLENGER, PROPOSER),
+            Err("Dispute already resolved".into())
+        );
+    }
+
+    #[test]
+    fn pages_are_clamped_to_the_list() {
+        let page = |count: u64, offset: u64, limit: u64| {
+            ArbiProofSimulator::page(U256::from(count), U256::from(offset), U256::from(limit))
+        };
+
+        assert_eq!(page(10, 0, 4), 0..4);
+        assert_eq!(page(10, 8, 4), 8..10);
+        assert_eq!(page(10, 12, 4), 10..10);
+        assert_eq!(page(500, 0, 1000), 0..MAX_PAGE_SIZE);
+        assert_eq!(page(10, 0, u64::MAX), 0..10);
+    }
+}
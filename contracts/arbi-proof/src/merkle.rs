@@ -0,0 +1,41 @@
+// Merkle proofs over execution-trace state hashes.
+//
+// Hashing follows OpenZeppelin's MerkleProof: inner nodes hash their two
+// children in sorted order, so a proof is just the list of siblings from the
+// leaf up. Sorted pairs do not encode a leaf's position, so the step index is
+// bound into the leaf itself, the way OpenZeppelin's StandardMerkleTree
+// encodes leaves:
+//
+//   leaf = keccak256(keccak256(abi.encode(uint256 step, bytes32 stateHash)))
+//
+// A tree built with `StandardMerkleTree.of(entries, ["uint256", "bytes32"])`
+// therefore verifies here, and with `MerkleProof.verify` on chain.
+
+use stylus_sdk::alloy_primitives::{FixedBytes, U256};
+use sha3::{Digest, Keccak256};
+
+// Leaf committing to the machine state hash after `step` steps
+pub fn step_leaf(step: U256, state_hash: FixedBytes<32>) -> FixedBytes<32> {
+    let mut inner = Keccak256::new();
+    inner.update(step.to_be_bytes::<32>());
+    inner.update(state_hash.as_slice());
+    FixedBytes::from_slice(&Keccak256::digest(inner.finalize()))
+}
+
+// Commutative node hash: keccak256 of the smaller child followed by the larger
+pub fn hash_pair(a: FixedBytes<32>, b: FixedBytes<32>) -> FixedBytes<32> {
+    let (first, second) = if a <= b { (a, b) } else { (b, a) };
+    let mut hasher = Keccak256::new();
+    hasher.update(first.as_slice());
+    hasher.update(second.as_slice());
+    FixedBytes::from_slice(&hasher.finalize())
+}
+
+// Root reached by folding the proof's siblings into the leaf
+pub fn process_proof(proof: &[FixedBytes<32>], leaf: FixedBytes<32>) -> FixedBytes<32> {
+    proof.iter().fold(leaf, |node, sibling| hash_pair(node, *sibling))
+}
+
+pub fn verify(proof: &[FixedBytes<32>], root: FixedBytes<32>, leaf: FixedBytes<32>) -> bool {
+    process_proof(proof, leaf) == root
+}
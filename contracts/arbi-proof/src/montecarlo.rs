@@ -0,0 +1,203 @@
+// Monte Carlo pricing of path-dependent options in 18-decimal fixed point.
+//
+// Paths follow geometric Brownian motion under the risk-neutral measure,
+// sampled at `steps` equally spaced monitoring dates:
+//
+//   S_{k+1} = S_k * exp((r - vol^2 / 2) * dt + vol * sqrt(dt) * Z_k)
+//
+// The normals come from Marsaglia's polar method over xoshiro256**, seeded
+// with keccak256 of the caller's seed, so a price is reproducible from its
+// inputs. Payoffs are tracked relative to spot to keep the running moments
+// in range, and the result is the discounted sample mean with its standard
+// error.
+
+use alloc::vec::Vec;
+use stylus_sdk::alloy_primitives::{FixedBytes, U256};
+use sha3::{Digest, Keccak256};
+
+use crate::fixed::{self, WAD};
+use crate::options::{self, Market};
+
+pub const MAX_PATHS: u32 = 10_000;
+pub const MAX_STEPS: u32 = 365;
+pub const MAX_SAMPLES: u64 = 200_000; // paths * steps
+
+// Barrier types, see `Payoff::Barrier`
+pub const BARRIER_UP_AND_OUT: u8 = 0;
+pub const BARRIER_DOWN_AND_OUT: u8 = 1;
+pub const BARRIER_UP_AND_IN: u8 = 2;
+pub const BARRIER_DOWN_AND_IN: u8 = 3;
+
+// xoshiro256** (Blackman and Vigna)
+pub struct Rng {
+    state: [u64; 4],
+}
+
+impl Rng {
+    pub fn new(seed: FixedBytes<32>) -> Self {
+        let digest = Keccak256::digest(seed.as_slice());
+        let mut state = [0u64; 4];
+        for (word, chunk) in state.iter_mut().zip(digest.chunks(8)) {
+            let mut bytes = [0u8; 8];
+            bytes.copy_from_slice(chunk);
+            *word = u64::from_be_bytes(bytes);
+        }
+        // The all-zero state is the generator's only fixed point
+        if state == [0; 4] {
+            state[0] = 1;
+        }
+        Self { state }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        let result = self.state[1].wrapping_mul(5).rotate_left(7).wrapping_mul(9);
+        let t = self.state[1] << 17;
+        self.state[2] ^= self.state[0];
+        self.state[3] ^= self.state[1];
+        self.state[1] ^= self.state[2];
+        self.state[0] ^= self.state[3];
+        self.state[2] ^= t;
+        self.state[3] = self.state[3].rotate_left(45);
+        result
+    }
+
+    // Uniform on [-1, 1), 60 bits of resolution
+    fn next_signed_unit(&mut self) -> i128 {
+        (((self.next_u64() >> 4) as i128 * 2 * WAD) >> 60) - WAD
+    }
+}
+
+// Standard normals by the polar method, which yields them in pairs
+pub struct NormalSampler {
+    rng: Rng,
+    spare: Option<i128>,
+}
+
+impl NormalSampler {
+    pub fn new(seed: FixedBytes<32>) -> Self {
+        Self { rng: Rng::new(seed), spare: None }
+    }
+
+    pub fn sample(&mut self) -> i128 {
+        if let Some(spare) = self.spare.take() {
+            return spare;
+        }
+        loop {
+            let u = self.rng.next_signed_unit();
+            let v = self.rng.next_signed_unit();
+            let s = fixed::mul(u, u) + fixed::mul(v, v);
+            if s == 0 || s >= WAD {
+                continue;
+            }
+            // s is in (0, 1), so ln(s) is defined and the root is real
+            let log = fixed::ln(s).unwrap_or(0);
+            let factor = fixed::sqrt(fixed::div(-2 * log, s)).unwrap_or(0);
+            self.spare = Some(fixed::mul(v, factor));
+            return fixed::mul(u, factor);
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Payoff {
+    // Arithmetic average of the prices at the monitoring dates
+    Asian { is_call: bool },
+    // Vanilla payoff at maturity, switched on or off by the price at any
+    // monitoring date, or spot, touching the barrier
+    Barrier { is_call: bool, kind: u8, barrier: i128 },
+}
+
+impl Payoff {
+    pub fn barrier(is_call: bool, kind: u8, barrier: U256) -> Result<Self, &'static str> {
+        if kind > BARRIER_DOWN_AND_IN {
+            return Err("Unknown barrier type");
+        }
+        Ok(Payoff::Barrier {
+            is_call,
+            kind,
+            barrier: fixed::bounded(barrier, options::MAX_PRICE, false, "Barrier out of range")?,
+        })
+    }
+}
+
+fn vanilla(is_call: bool, price: i128, strike: i128) -> i128 {
+    if is_call {
+        (price - strike).max(0)
+    } else {
+        (strike - price).max(0)
+    }
+}
+
+// Discounted price and standard error
+pub fn price(
+    market: &Market,
+    payoff: Payoff,
+    paths: u32,
+    steps: u32,
+    seed: FixedBytes<32>,
+) -> Result<(i128, i128), &'static str> {
+    if !(2..=MAX_PATHS).contains(&paths) || !(1..=MAX_STEPS).contains(&steps) {
+        return Err("Unsupported path count");
+    }
+    if paths as u64 * steps as u64 > MAX_SAMPLES {
+        return Err("Too many samples");
+    }
+
+    let dt = market.time / steps as i128;
+    let diffusion = fixed::mul(market.vol, fixed::sqrt(dt)?);
+    let drift = fixed::mul(market.rate - fixed::mul(market.vol, market.vol) / 2, dt);
+    let strike = fixed::div(market.strike, market.spot);
+
+    // Barrier level as ln(barrier / spot), to compare against log prices
+    let level = match payoff {
+        Payoff::Barrier { barrier, .. } => fixed::ln(barrier)? - fixed::ln(market.spot)?,
+        Payoff::Asian { .. } => 0,
+    };
+
+    let mut sampler = NormalSampler::new(seed);
+    let mut log_prices = Vec::with_capacity(steps as usize);
+
+    // Welford's running mean and sum of squared deviations, in units of spot
+    let (mut mean, mut m2) = (0i128, 0i128);
+    for path in 1..=paths as i128 {
+        log_prices.clear();
+        let mut log_price = 0;
+        for _ in 0..steps {
+            log_price += drift + fixed::mul(diffusion, sampler.sample());
+            log_prices.push(log_price);
+        }
+
+        let value = match payoff {
+            Payoff::Asian { is_call } => {
+                let mut sum = 0;
+                for log_price in &log_prices {
+                    sum += fixed::exp(*log_price).map_err(|_| "Path out of range")?;
+                }
+                vanilla(is_call, sum / steps as i128, strike)
+            }
+            Payoff::Barrier { is_call, kind, .. } => {
+                let up = kind == BARRIER_UP_AND_OUT || kind == BARRIER_UP_AND_IN;
+                let touched = core::iter::once(&0)
+                    .chain(log_prices.iter())
+                    .any(|log_price| if up { *log_price >= level } else { *log_price <= level });
+                let knock_out = kind == BARRIER_UP_AND_OUT || kind == BARRIER_DOWN_AND_OUT;
+                if touched != knock_out {
+                    let terminal = fixed::exp(log_price).map_err(|_| "Path out of range")?;
+                    vanilla(is_call, terminal, strike)
+                } else {
+                    0
+                }
+            }
+        };
+
+        let delta = value - mean;
+        mean += delta / path;
+        m2 += fixed::mul(delta, value - mean);
+    }
+
+    let discount = market.discount()?;
+    let variance = m2 / (paths as i128 - 1);
+    let std_error = fixed::sqrt(variance / paths as i128)?;
+    let scale = |value: i128| fixed::mul(fixed::mul(value, discount), market.spot);
+    Ok((scale(mean), scale(std_error)))
+}
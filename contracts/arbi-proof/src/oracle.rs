@@ -0,0 +1,59 @@
+// Realized volatility of a pool's price, in 18-decimal fixed point.
+//
+// After every swap the pool manager reports the pool price. Each new
+// observation contributes its squared log return, annualized by the time
+// since the previous one:
+//
+//   sample = ln(p_t / p_{t-1})^2 * SECONDS_PER_YEAR / (t - (t-1))
+//
+// and the variance is the exponentially weighted moving average
+//
+//   var_t = DECAY * var_{t-1} + (1 - DECAY) * sample
+//
+// with the RiskMetrics decay of 0.94. An observation in the same second as
+// the latest one replaces that second's close and the second's sample is
+// taken again, so one block's swaps count as a single return up to its last
+// price and a sandwich inside a block cannot pump the estimate. The fee then
+// rises linearly with the volatility, sqrt(var_t), up to a cap.
+
+use stylus_sdk::alloy_primitives::U256;
+
+use crate::fixed::{self, WAD};
+
+// Observations kept per pool
+pub const OBSERVATION_CAPACITY: u64 = 64;
+
+pub const EWMA_DECAY: i128 = 940_000_000_000_000_000;
+pub const SECONDS_PER_YEAR: i128 = 31_536_000;
+
+// Swap fees in thousandths of a percent, i.e. units of 1e-5 (300 = 0.3%)
+pub const BASE_SWAP_FEE: u64 = 300;
+// Added per 100% of annualized volatility (1_000 = 1%)
+pub const VOLATILITY_FEE_SLOPE: u64 = 1_000;
+pub const MAX_SWAP_FEE: u64 = 5_000; // 5_000 = 5%
+
+pub fn parse_price(price: U256) -> Result<i128, &'static str> {
+    fixed::bounded(price, i128::MAX, false, "Price out of range")
+}
+
+// Variance after a move from `previous` to `price` over `elapsed` seconds.
+// Prices may be in any unit, only their ratio matters.
+pub fn update_variance(variance: i128, previous: i128, price: i128, elapsed: u64) -> Result<i128, &'static str> {
+    if elapsed == 0 {
+        return Ok(variance);
+    }
+    let log_return = fixed::ln(price)? - fixed::ln(previous)?;
+    let sample = fixed::mul(log_return, log_return) * SECONDS_PER_YEAR / elapsed as i128;
+    Ok(fixed::mul(EWMA_DECAY, variance) + fixed::mul(WAD - EWMA_DECAY, sample))
+}
+
+// Annualized volatility
+pub fn volatility(variance: i128) -> Result<i128, &'static str> {
+    fixed::sqrt(variance)
+}
+
+pub fn dynamic_fee(volatility: i128) -> u64 {
+    let premium = U256::from(volatility.max(0) as u128) * U256::from(VOLATILITY_FEE_SLOPE) / U256::from(WAD as u128);
+    let cap = U256::from(MAX_SWAP_FEE - BASE_SWAP_FEE);
+    BASE_SWAP_FEE + premium.min(cap).to::<u64>()
+}
@@ -0,0 +1,257 @@
+// Native stand-in for the Stylus host, so unit tests can drive the contract
+// through its entrypoints.
+//
+// The SDK caches msg::sender, msg::value and block::timestamp for the whole
+// process, so lib.rs reads them from the `msg` and `block` modules below in
+// test builds. Storage, logs, ETH transfers and the ecrecover precompile are
+// served by the `vm_hooks` imports exported at the bottom. The SDK's storage
+// cache is a process-wide global, so every `Chain` holds a lock for its
+// lifetime and stateful tests run one at a time.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::string::String;
+use std::sync::{Mutex, MutexGuard};
+use stylus_sdk::{
+    alloy_primitives::{Address, FixedBytes, U256},
+    storage::{StorageCache, StorageType},
+};
+use sha3::{Digest, Keccak256};
+
+use crate::ArbiProofSimulator;
+
+// Account allowed to initialize the contract in tests
+pub const DEPLOYER: Address = Address::with_last_byte(0xde);
+
+pub const CHAIN_ID: u64 = 42161;
+pub const CONTRACT: Address = Address::with_last_byte(0xc0);
+
+#[derive(Default, Clone)]
+struct Host {
+    storage: HashMap<[u8; 32], [u8; 32]>,
+    sender: Address,
+    value: U256,
+    timestamp: u64,
+    transfers: Vec<(Address, U256)>,
+    logs: Vec<Vec<FixedBytes<32>>>,
+    return_data: Vec<u8>,
+}
+
+thread_local! {
+    static HOST: RefCell<Host> = RefCell::new(Host::default());
+}
+
+static LOCK: Mutex<()> = Mutex::new(());
+
+pub mod msg {
+    use super::HOST;
+    use stylus_sdk::alloy_primitives::{Address, U256};
+
+    pub fn sender() -> Address {
+        HOST.with(|host| host.borrow().sender)
+    }
+
+    pub fn value() -> U256 {
+        HOST.with(|host| host.borrow().value)
+    }
+}
+
+pub mod block {
+    use super::{CHAIN_ID, HOST};
+
+    pub fn timestamp() -> u64 {
+        HOST.with(|host| host.borrow().timestamp)
+    }
+
+    pub fn chainid() -> u64 {
+        CHAIN_ID
+    }
+}
+
+// Signature that the mock ecrecover attributes to `signer`: r holds the
+// address, s is small and v is 27
+pub fn signature_of(signer: Address) -> Vec<u8> {
+    let mut signature = vec![0u8; 65];
+    signature[12..32].copy_from_slice(signer.as_slice());
+    signature[63] = 1;
+    signature[64] = 27;
+    signature
+}
+
+// A fresh contract on an empty chain
+pub struct Chain {
+    pub contract: ArbiProofSimulator,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Chain {
+    pub fn new() -> Self {
+        // A test that panicked mid-call poisons the lock, not the next test
+        let lock = LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        StorageCache::clear();
+        HOST.with(|host| {
+            *host.borrow_mut() = Host {
+                timestamp: 1_700_000_000,
+                ..Host::default()
+            }
+        });
+
+        Self {
+            contract: unsafe { ArbiProofSimulator::new(U256::ZERO, 0) },
+            _lock: lock,
+        }
+    }
+
+    // Runs one transaction from `from` carrying `value`; a failed one leaves
+    // no trace, like a revert
+    pub fn call<T>(
+        &mut self,
+        from: Address,
+        value: u64,
+        call: impl FnOnce(&mut ArbiProofSimulator) -> Result<T, Vec<u8>>,
+    ) -> Result<T, String> {
+        let snapshot = HOST.with(|host| {
+            let mut host = host.borrow_mut();
+            host.sender = from;
+            host.value = U256::from(value);
+            host.clone()
+        });
+
+        let result = call(&mut self.contract);
+        StorageCache::clear();
+
+        result.map_err(|error| {
+            HOST.with(|host| *host.borrow_mut() = snapshot);
+            String::from_utf8(error).unwrap()
+        })
+    }
+
+    pub fn warp(&mut self, seconds: u64) {
+        HOST.with(|host| host.borrow_mut().timestamp += seconds);
+    }
+
+    pub fn timestamp(&self) -> u64 {
+        block::timestamp()
+    }
+
+    // Wei sent out to `account` so far
+    pub fn received(&self, account: Address) -> U256 {
+        HOST.with(|host| {
+            host.borrow()
+                .transfers
+                .iter()
+                .filter(|(to, _)| *to == account)
+                .map(|(_, amount)| *amount)
+                .sum()
+        })
+    }
+
+    // Number of logs emitted with `topic0`
+    pub fn emitted(&self, topic0: FixedBytes<32>) -> usize {
+        HOST.with(|host| {
+            host.borrow()
+                .logs
+                .iter()
+                .filter(|topics| topics.first() == Some(&topic0))
+                .count()
+        })
+    }
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn storage_load_bytes32(key: *const u8, dest: *mut u8) {
+    let key = *(key as *const [u8; 32]);
+    let value = HOST.with(|host| host.borrow().storage.get(&key).copied().unwrap_or_default());
+    std::ptr::copy_nonoverlapping(value.as_ptr(), dest, 32);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn storage_store_bytes32(key: *const u8, value: *const u8) {
+    let key = *(key as *const [u8; 32]);
+    let value = *(value as *const [u8; 32]);
+    HOST.with(|host| host.borrow_mut().storage.insert(key, value));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn native_keccak256(bytes: *const u8, len: usize, output: *mut u8) {
+    let digest = Keccak256::digest(std::slice::from_raw_parts(bytes, len));
+    std::ptr::copy_nonoverlapping(digest.as_ptr(), output, 32);
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn emit_log(data: *const u8, len: usize, topics: usize) {
+    let data = std::slice::from_raw_parts(data, len);
+    let topics = data[..32 * topics].chunks(32).map(FixedBytes::<32>::from_slice).collect();
+    HOST.with(|host| host.borrow_mut().logs.push(topics));
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn contract_address(address: *mut u8) {
+    std::ptr::copy_nonoverlapping(CONTRACT.as_ptr(), address, 20);
+}
+
+// Every call is a plain ETH transfer that succeeds
+#[no_mangle]
+pub unsafe extern "C" fn call_contract(
+    contract: *const u8,
+    _calldata: *const u8,
+    _calldata_len: usize,
+    value: *const u8,
+    _gas: u64,
+    return_data_len: *mut usize,
+) -> u8 {
+    let to = Address::from_slice(std::slice::from_raw_parts(contract, 20));
+    let value = U256::from_be_slice(std::slice::from_raw_parts(value, 32));
+    HOST.with(|host| {
+        let mut host = host.borrow_mut();
+        host.transfers.push((to, value));
+        host.return_data.clear();
+    });
+    *return_data_len = 0;
+    0
+}
+
+// Only ecrecover is reachable; it returns the address held in r
+#[no_mangle]
+pub unsafe extern "C" fn static_call_contract(
+    _contract: *const u8,
+    calldata: *const u8,
+    calldata_len: usize,
+    _gas: u64,
+    return_data_len: *mut usize,
+) -> u8 {
+    let input = std::slice::from_raw_parts(calldata, calldata_len);
+    let mut output = vec![0u8; 32];
+    output[12..].copy_from_slice(&input[76..96]);
+    HOST.with(|host| host.borrow_mut().return_data = output);
+    *return_data_len = 32;
+    0
+}
+
+#[no_mangle]
+pub unsafe extern "C" fn read_return_data(dest: *mut u8, offset: usize, size: usize) -> usize {
+    HOST.with(|host| {
+        let host = host.borrow();
+        let data = &host.return_data[offset.min(host.return_data.len())..];
+        let size = size.min(data.len());
+        std::ptr::copy_nonoverlapping(data.as_ptr(), dest, size);
+        size
+    })
+}
+
+#[no_mangle]
+pub extern "C" fn return_data_size() -> usize {
+    HOST.with(|host| host.borrow().return_data.len())
+}
+
+// Referenced by RawCall, never reached by the contract
+#[no_mangle]
+pub extern "C" fn delegate_call_contract(
+    _contract: *const u8,
+    _calldata: *const u8,
+    _calldata_len: usize,
+    _gas: u64,
+    _return_data_len: *mut usize,
+) -> u8 {
+    unreachable!("delegate calls are not supported")
+}
@@ -0,0 +1,189 @@
+// Minimal register machine used to settle single-step disputes.
+//
+// A machine state is the program counter, four 256-bit registers and the
+// Merkle root of the program's code. Its hash is what parties claim at every
+// step of the execution trace, so once bisection narrows a dispute down to a
+// single step the contract can replay that one instruction and see whose
+// post-state hash is right.
+//
+// State encoding (168 bytes):
+//   pc (8, big endian) | r0 | r1 | r2 | r3 (32 each) | code_root (32)
+//
+// Instruction encoding (8 bytes):
+//   opcode | dst | a | b | imm (4, big endian)
+//
+// Register operands only use their two low bits, and unknown opcodes as well
+// as a pc past the end of the code behave like HALT, so every well-formed
+// instruction has a defined post-state.
+
+use alloc::vec::Vec;
+use stylus_sdk::alloy_primitives::{FixedBytes, U256};
+use sha3::{Digest, Keccak256};
+
+pub const REGISTER_COUNT: usize = 4;
+pub const STATE_SIZE: usize = 8 + 32 * REGISTER_COUNT + 32;
+pub const INSTRUCTION_SIZE: usize = 8;
+
+// Instruction set
+pub const OP_NOP: u8 = 0x00;
+pub const OP_LOADI: u8 = 0x01; // dst = imm
+pub const OP_ADD: u8 = 0x02; // dst = a + b (wrapping)
+pub const OP_SUB: u8 = 0x03; // dst = a - b (wrapping)
+pub const OP_MUL: u8 = 0x04; // dst = a * b (wrapping)
+pub const OP_AND: u8 = 0x05; // dst = a & b
+pub const OP_OR: u8 = 0x06; // dst = a | b
+pub const OP_XOR: u8 = 0x07; // dst = a ^ b
+pub const OP_JUMP: u8 = 0x08; // pc = imm
+pub const OP_JUMPI: u8 = 0x09; // pc = imm if a != 0
+pub const OP_HALT: u8 = 0x0a; // state is left unchanged
+
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct MachineState {
+    pub pc: u64,
+    pub registers: [U256; REGISTER_COUNT],
+    pub code_root: FixedBytes<32>,
+}
+
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Instruction {
+    pub opcode: u8,
+    pub dst: u8,
+    pub a: u8,
+    pub b: u8,
+    pub imm: u32,
+}
+
+impl MachineState {
+    pub fn decode(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != STATE_SIZE {
+            return Err("Malformed machine state");
+        }
+
+        let mut pc = [0u8; 8];
+        pc.copy_from_slice(&bytes[..8]);
+
+        let mut registers = [U256::ZERO; REGISTER_COUNT];
+        for (i, register) in registers.iter_mut().enumerate() {
+            let offset = 8 + 32 * i;
+            *register = U256::from_be_slice(&bytes[offset..offset + 32]);
+        }
+
+        Ok(Self {
+            pc: u64::from_be_bytes(pc),
+            registers,
+            code_root: FixedBytes::from_slice(&bytes[STATE_SIZE - 32..]),
+        })
+    }
+
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(STATE_SIZE);
+        bytes.extend_from_slice(&self.pc.to_be_bytes());
+        for register in self.registers.iter() {
+            bytes.extend_from_slice(&register.to_be_bytes::<32>());
+        }
+        bytes.extend_from_slice(self.code_root.as_slice());
+        bytes
+    }
+
+    pub fn hash(&self) -> FixedBytes<32> {
+        FixedBytes::from_slice(&Keccak256::digest(self.encode()))
+    }
+}
+
+impl Instruction {
+    pub fn decode(bytes: &[u8]) -> Result<Self, &'static str> {
+        if bytes.len() != INSTRUCTION_SIZE {
+            return Err("Malformed instruction");
+        }
+
+        Ok(Self {
+            opcode: bytes[0],
+            dst: bytes[1],
+            a: bytes[2],
+            b: bytes[3],
+            imm: u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+        })
+    }
+}
+
+// Leaf of the code tree for the instruction at `pc`
+pub fn code_leaf(instruction: &[u8]) -> FixedBytes<32> {
+    FixedBytes::from_slice(&Keccak256::digest(instruction))
+}
+
+// Folds a positional Merkle path: bit i of `index` says whether the node at
+// level i is a right child
+pub fn code_root_from_proof(leaf: FixedBytes<32>, index: u64, proof: &[FixedBytes<32>]) -> FixedBytes<32> {
+    let mut node = leaf;
+    for (level, sibling) in proof.iter().enumerate() {
+        let mut hasher = Keccak256::new();
+        if level < 64 && (index >> level) & 1 == 1 {
+            hasher.update(sibling.as_slice());
+            hasher.update(node.as_slice());
+        } else {
+            hasher.update(node.as_slice());
+            hasher.update(sibling.as_slice());
+        }
+        node = FixedBytes::from_slice(&hasher.finalize());
+    }
+    node
+}
+
+// Applies a single instruction to the machine state
+pub fn step(state: &MachineState, instruction: &Instruction) -> MachineState {
+    let mut next = *state;
+    let reg = |r: u8| (r as usize) % REGISTER_COUNT;
+    let a = state.registers[reg(instruction.a)];
+    let b = state.registers[reg(instruction.b)];
+    let dst = reg(instruction.dst);
+
+    match instruction.opcode {
+        OP_NOP => {}
+        OP_LOADI => next.registers[dst] = U256::from(instruction.imm),
+        OP_ADD => next.registers[dst] = a.wrapping_add(b),
+        OP_SUB => next.registers[dst] = a.wrapping_sub(b),
+        OP_MUL => next.registers[dst] = a.wrapping_mul(b),
+        OP_AND => next.registers[dst] = a & b,
+        OP_OR => next.registers[dst] = a | b,
+        OP_XOR => next.registers[dst] = a ^ b,
+        OP_JUMP => {
+            next.pc = instruction.imm as u64;
+            return next;
+        }
+        OP_JUMPI => {
+            if a != U256::ZERO {
+                next.pc = instruction.imm as u64;
+                return next;
+            }
+        }
+        // HALT and anything unknown keep the machine where it is
+        OP_HALT => return next,
+        _ => return next,
+    }
+
+    next.pc = state.pc.wrapping_add(1);
+    next
+}
+
+// Checks the instruction against the code root committed in the pre-state,
+// executes it and returns the post-state hash
+pub fn execute_one_step(
+    pre_state: &[u8],
+    instruction: &[u8],
+    proof: &[FixedBytes<32>],
+) -> Result<FixedBytes<32>, &'static str> {
+    let state = MachineState::decode(pre_state)?;
+    let decoded = Instruction::decode(instruction)?;
+
+    // The path folds only the low bits of the pc, so a pc past the end of the
+    // code is shown with the instruction it wraps onto. That only pins down
+    // the code size, which is all a halted machine needs.
+    if code_root_from_proof(code_leaf(instruction), state.pc, proof) != state.code_root {
+        return Err("Instruction not in program");
+    }
+    if proof.len() < 64 && state.pc >> proof.len() != 0 {
+        return Ok(state.hash());
+    }
+
+    Ok(step(&state, &decoded).hash())
+}
@@ -1,5 +1,14 @@
 import React, { useState, useEffect } from 'react';
 import { BarChart, Bar, XAxis, YAxis, CartesianGrid, Tooltip, Legend, ResponsiveContainer, LineChart, Line } from 'recharts';
+import { ethers } from 'ethers';
+import { ArbiProofABI } from '../contracts/ArbiProofABI';
+
+// Set this to your deployed contract address on Arbitrum Stylus testnet
+const CONTRACT_ADDRESS = '0x0000000000000000000000000000000000000000';
+
+// The contract takes and returns 18-decimal fixed point
+const toWad = (value: number) => ethers.parseUnits(value.toFixed(18), 18);
+const fromWad = (value: bigint) => Number(ethers.formatUnits(value, 18));
 
 interface FinancialComputationsProps {
   account: string | null;
@@ -13,9 +22,11 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
     strikePrice: 100,
     volatility: 20,
     riskFreeRate: 5,
-    timeToExpiry: 1
+    timeToExpiry: 1,
+    marketPrice: 10
   });
   const [optionResults, setOptionResults] = useState<any>(null);
+  const [error, setError] = useState<string | null>(null);
   
   const [yieldParams, setYieldParams] = useState({
     initialDeposit: 10000,
@@ -38,152 +49,124 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
     }
   };
 
-  // Calculate option prices using Black-Scholes formula (simplified version)
-  const calculateBlackScholes = () => {
+  // Prices, greeks and implied volatility all come from the contract's
+  // fixed-point Black-Scholes entrypoints
+  const calculateBlackScholes = async () => {
+    if (!account || !window.ethereum) {
+      setError('Connect your wallet to query the contract');
+      return;
+    }
+
     setLoading(true);
-    
-    setTimeout(() => {
-      // Convert inputs to the proper format
-      const S = optionParams.stockPrice; // Stock price
-      const K = optionParams.strikePrice; // Strike price
-      const v = optionParams.volatility / 100; // Volatility
-      const r = optionParams.riskFreeRate / 100; // Risk-free rate
-      const T = optionParams.timeToExpiry; // Time to expiry in years
-      
-      // Simple approximation of Black-Scholes
-      const d1 = (Math.log(S/K) + (r + v*v/2) * T) / (v * Math.sqrt(T));
-      const d2 = d1 - v * Math.sqrt(T);
-      
-      // Simplified normal distribution approximation
-      const normDist = (x: number) => {
-        const a1 = 0.254829592;
-        const a2 = -0.284496736;
-        const a3 = 1.421413741;
-        const a4 = -1.453152027;
-        const a5 = 1.061405429;
-        const p = 0.3275911;
-        
-        const sign = (x < 0) ? -1 : 1;
-        x = Math.abs(x) / Math.sqrt(2.0);
-        
-        const t = 1.0 / (1.0 + p * x);
-        const y = 1.0 - (((((a5 * t + a4) * t) + a3) * t + a2) * t + a1) * t * Math.exp(-x * x);
-        
-        return 0.5 * (1.0 + sign * y);
-      };
-      
-      const callPrice = S * normDist(d1) - K * Math.exp(-r * T) * normDist(d2);
-      const putPrice = K * Math.exp(-r * T) * normDist(-d2) - S * normDist(-d1);
-      
-      // Generate sensitivity analysis data
-      const generatePricePoints = () => {
-        const points = [];
-        const basePrice = optionParams.stockPrice;
-        
-        for (let i = -30; i <= 30; i += 5) {
-          const price = basePrice * (1 + i/100);
-          const adjustedD1 = (Math.log(price/K) + (r + v*v/2) * T) / (v * Math.sqrt(T));
-          const adjustedD2 = adjustedD1 - v * Math.sqrt(T);
-          const adjustedCall = price * normDist(adjustedD1) - K * Math.exp(-r * T) * normDist(adjustedD2);
-          const adjustedPut = K * Math.exp(-r * T) * normDist(-adjustedD2) - price * normDist(-adjustedD1);
-          
-          points.push({
+    setError(null);
+    try {
+      const contract = new ethers.Contract(CONTRACT_ADDRESS, ArbiProofABI, new ethers.BrowserProvider(window.ethereum));
+      const spot = toWad(optionParams.stockPrice);
+      const strike = toWad(optionParams.strikePrice);
+      const rate = toWad(optionParams.riskFreeRate / 100);
+      const vol = toWad(optionParams.volatility / 100);
+      const time = toWad(optionParams.timeToExpiry);
+
+      const started = performance.now();
+      const [callPrice, putPrice, greeks, impliedVol, gasUsed] = await Promise.all([
+        contract.price_option(spot, strike, rate, vol, time, true),
+        contract.price_option(spot, strike, rate, vol, time, false),
+        contract.option_greeks(spot, strike, rate, vol, time, true),
+        contract.implied_volatility(toWad(optionParams.marketPrice), spot, strike, rate, time, true),
+        contract.option_greeks.estimateGas(spot, strike, rate, vol, time, true)
+      ]);
+      const executionTime = Math.round(performance.now() - started);
+
+      // Sensitivity analysis over spot prices up to 30% either side
+      const pricePoints = await Promise.all(
+        Array.from({ length: 13 }, (_, i) => optionParams.stockPrice * (1 + (i * 5 - 30) / 100)).map(async (price) => {
+          const [call, put] = await Promise.all([
+            contract.price_option(toWad(price), strike, rate, vol, time, true),
+            contract.price_option(toWad(price), strike, rate, vol, time, false)
+          ]);
+          return {
             stockPrice: price.toFixed(2),
-            callPrice: adjustedCall.toFixed(2),
-            putPrice: adjustedPut.toFixed(2)
-          });
-        }
-        
-        return points;
-      };
-      
-      // Calculate implied volatility (simplified)
-      const impliedVol = v + (callPrice > K * 0.1 ? 0.02 : -0.01);
-      
-      // Generate greeks
-      const delta = normDist(d1);
-      const gamma = Math.exp(-d1*d1/2) / (S * v * Math.sqrt(T) * Math.sqrt(2 * Math.PI));
-      const theta = -(S * v * Math.exp(-d1*d1/2)) / (2 * Math.sqrt(T) * Math.sqrt(2 * Math.PI)) - r * K * Math.exp(-r * T) * normDist(d2);
-      const vega = S * Math.sqrt(T) * Math.exp(-d1*d1/2) / Math.sqrt(2 * Math.PI);
-      
+            callPrice: fromWad(call).toFixed(2),
+            putPrice: fromWad(put).toFixed(2)
+          };
+        })
+      );
+
       setOptionResults({
-        callPrice: callPrice.toFixed(2),
-        putPrice: putPrice.toFixed(2),
-        impliedVolatility: (impliedVol * 100).toFixed(2),
+        callPrice: fromWad(callPrice).toFixed(2),
+        putPrice: fromWad(putPrice).toFixed(2),
+        impliedVolatility: (fromWad(impliedVol) * 100).toFixed(2),
         greeks: {
-          delta: delta.toFixed(4),
-          gamma: gamma.toFixed(4), 
-          theta: theta.toFixed(4),
-          vega: vega.toFixed(4)
+          delta: fromWad(greeks.delta).toFixed(4),
+          gamma: fromWad(greeks.gamma).toFixed(4),
+          vega: fromWad(greeks.vega).toFixed(4),
+          theta: fromWad(greeks.theta).toFixed(4),
+          rho: fromWad(greeks.rho).toFixed(4)
         },
-        pricePoints: generatePricePoints(),
-        executionTime: Math.floor(Math.random() * 10) + 25, // Simulated execution time in ms
-        gasUsed: gasBenchmarks.options.stylus
+        pricePoints,
+        executionTime,
+        gasUsed: Number(gasUsed)
       });
-      
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Contract call failed');
+      setOptionResults(null);
+    } finally {
       setLoading(false);
-    }, 1200);
+    }
   };
   
-  // Calculate yield optimization scenarios (simplified simulation)
-  const calculateYieldOptimization = () => {
+  // Strategies offered to the optimizer, each with an APY (%) and a risk
+  // score from 1 to 5
+  const strategies = [
+    { name: 'Lending', apy: 3.5, risk: 1 },
+    { name: 'Staking', apy: 6.4, risk: 2 },
+    { name: 'LP Provision', apy: 8.2, risk: 3 },
+    { name: 'Yield Farming', apy: 12.8, risk: 4 },
+    { name: 'Options Writing', apy: 18.5, risk: 5 }
+  ];
+
+  // Highest average risk score the deposit may carry
+  const riskBudgets: Record<string, number> = { low: 1.5, medium: 2.5, high: 4 };
+
+  // The allocation comes from the contract's optimize_yield entrypoint; only
+  // the balance projection is compounded here
+  const calculateYieldOptimization = async () => {
+    if (!account || !window.ethereum) {
+      setError('Connect your wallet to query the contract');
+      return;
+    }
+
     setLoading(true);
-    
-    setTimeout(() => {
+    setError(null);
+    try {
+      const contract = new ethers.Contract(CONTRACT_ADDRESS, ArbiProofABI, new ethers.BrowserProvider(window.ethereum));
       const initialDeposit = yieldParams.initialDeposit;
       const days = yieldParams.duration;
-      
-      // Different APY configurations based on optimization level
-      const strategyComplexity = yieldParams.optimizationLevel === 'low' ? 10 : 
-                                yieldParams.optimizationLevel === 'medium' ? 30 : 75;
-      
-      // Generate strategies (simplified)
-      const strategies = [
-        { name: 'Lending', baseAPY: 3.5, risk: 'Low' },
-        { name: 'LP Provision', baseAPY: 8.2, risk: 'Medium' },
-        { name: 'Staking', baseAPY: 6.4, risk: 'Medium-Low' },
-        { name: 'Yield Farming', baseAPY: 12.8, risk: 'Medium-High' },
-        { name: 'Options Writing', baseAPY: 18.5, risk: 'High' }
+
+      // No single strategy takes more than half of the deposit
+      const args = [
+        toWad(initialDeposit),
+        strategies.map(strategy => toWad(strategy.apy / 100)),
+        strategies.map(strategy => toWad(strategy.risk)),
+        strategies.map(() => toWad(initialDeposit / 2)),
+        toWad(riskBudgets[yieldParams.optimizationLevel])
       ];
-      
-      // Create portfolio allocation based on optimization level
-      const generateAllocation = () => {
-        if (yieldParams.optimizationLevel === 'low') {
-          return [
-            { strategy: 'Lending', allocation: 70 },
-            { strategy: 'Staking', allocation: 30 }
-          ];
-        } else if (yieldParams.optimizationLevel === 'medium') {
-          return [
-            { strategy: 'Lending', allocation: 40 },
-            { strategy: 'Staking', allocation: 30 },
-            { strategy: 'LP Provision', allocation: 30 }
-          ];
-        } else {
-          return [
-            { strategy: 'Lending', allocation: 20 },
-            { strategy: 'Staking', allocation: 20 },
-            { strategy: 'LP Provision', allocation: 30 },
-            { strategy: 'Yield Farming', allocation: 20 },
-            { strategy: 'Options Writing', allocation: 10 }
-          ];
-        }
-      };
-      
-      const allocation = generateAllocation();
-      
-      // Calculate blended APY
-      let blendedAPY = 0;
-      allocation.forEach(item => {
-        const strategy = strategies.find(s => s.name === item.strategy);
-        if (strategy) {
-          blendedAPY += (strategy.baseAPY * (item.allocation / 100));
-        }
-      });
-      
-      // Add a small randomization for realism
-      blendedAPY = blendedAPY * (1 + (Math.random() * 0.15 - 0.075));
-      
+
+      const started = performance.now();
+      const [[amounts, blendedApy], gasUsed] = await Promise.all([
+        contract.optimize_yield(...args),
+        contract.optimize_yield.estimateGas(...args)
+      ]);
+      const executionTime = Math.round(performance.now() - started);
+
+      const allocation = strategies
+        .map((strategy, i) => ({
+          strategy: strategy.name,
+          allocation: (fromWad(amounts[i]) / initialDeposit * 100).toFixed(1)
+        }))
+        .filter(item => Number(item.allocation) > 0);
+      const blendedAPY = fromWad(blendedApy) * 100;
+
       // Generate projection data
       const generateProjection = () => {
         const points = [];
@@ -215,13 +198,16 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
         dailyAverage: (profit / days).toFixed(2),
         allocation,
         projection: generateProjection(),
-        strategies: strategyComplexity,
-        executionTime: Math.floor(Math.random() * 15) + 30, // Simulated execution time in ms
-        gasUsed: gasBenchmarks.yield.stylus
+        strategies: strategies.length,
+        executionTime,
+        gasUsed: Number(gasUsed)
       });
-      
+    } catch (err) {
+      setError(err instanceof Error ? err.message : 'Contract call failed');
+      setYieldResults(null);
+    } finally {
       setLoading(false);
-    }, 1200);
+    }
   };
   
   const handleChangeOptionParam = (param: keyof typeof optionParams, value: string) => {
@@ -357,6 +343,21 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     className="w-full px-4 py-2 bg-slate-900 border border-slate-700 rounded-lg text-slate-300 focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent"
                   />
                 </div>
+                
+                <div>
+                  <label className="block text-sm font-medium text-slate-400 mb-1">Market Call Price ($)</label>
+                  <input
+                    type="number"
+                    value={optionParams.marketPrice}
+                    onChange={(e) => handleChangeOptionParam('marketPrice', e.target.value)}
+                    min="0"
+                    step="0.1"
+                    className="w-full px-4 py-2 bg-slate-900 border border-slate-700 rounded-lg text-slate-300 focus:outline-none focus:ring-2 focus:ring-amber-500 focus:border-transparent"
+                  />
+                  <p className="mt-2 text-xs text-slate-500">
+                    The implied volatility is solved from this price
+                  </p>
+                </div>
               </div>
               
               <div className="mt-8">
@@ -375,6 +376,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     </span>
                   ) : 'Calculate Option Prices'}
                 </button>
+                {error && <p className="mt-3 text-sm text-red-400">{error}</p>}
               </div>
               
               <div className="mt-6 bg-slate-900/60 p-4 rounded-lg border border-slate-800">
@@ -420,7 +422,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                 </div>
               ) : (
                 <div className="space-y-6">
-                  <div className="grid grid-cols-2 gap-4">
+                  <div className="grid grid-cols-3 gap-4">
                     <div className="bg-slate-900/60 p-4 rounded-lg border border-slate-800">
                       <p className="text-sm text-slate-500 mb-1">Call Option Price</p>
                       <p className="text-2xl font-bold text-white">${optionResults.callPrice}</p>
@@ -429,6 +431,10 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                       <p className="text-sm text-slate-500 mb-1">Put Option Price</p>
                       <p className="text-2xl font-bold text-white">${optionResults.putPrice}</p>
                     </div>
+                    <div className="bg-slate-900/60 p-4 rounded-lg border border-slate-800">
+                      <p className="text-sm text-slate-500 mb-1">Implied Volatility</p>
+                      <p className="text-2xl font-bold text-white">{optionResults.impliedVolatility}%</p>
+                    </div>
                   </div>
                   
                   <div>
@@ -453,8 +459,8 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                   </div>
                   
                   <div>
-                    <h4 className="text-sm font-medium text-slate-400 mb-3">Option Greeks</h4>
-                    <div className="grid grid-cols-2 sm:grid-cols-4 gap-2 mb-4">
+                    <h4 className="text-sm font-medium text-slate-400 mb-3">Call Option Greeks</h4>
+                    <div className="grid grid-cols-2 sm:grid-cols-5 gap-2 mb-4">
                       {Object.entries(optionResults.greeks).map(([name, value]) => (
                         <div key={name} className="bg-slate-900/80 rounded p-2 text-center">
                           <p className="text-xs text-slate-500 capitalize mb-1">{name}</p>
@@ -468,7 +474,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     <div>
                       <p className="text-xs font-medium text-amber-300 uppercase tracking-wider">Execution Stats</p>
                       <p className="text-sm text-slate-300">
-                        <span className="font-mono text-amber-400">{optionResults.executionTime}ms</span> on-chain execution time
+                        <span className="font-mono text-amber-400">{optionResults.executionTime}ms</span> round trip to the contract
                       </p>
                     </div>
                     <div className="bg-amber-900/30 px-3 py-1 rounded-full">
@@ -528,7 +534,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                 </div>
                 
                 <div>
-                  <label className="block text-sm font-medium text-slate-400 mb-1">Optimization Complexity</label>
+                  <label className="block text-sm font-medium text-slate-400 mb-1">Risk Appetite</label>
                   <div className="flex justify-between p-1 bg-slate-900 rounded-lg">
                     {(['low', 'medium', 'high'] as const).map((level) => (
                       <button
@@ -546,7 +552,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     ))}
                   </div>
                   <p className="mt-2 text-xs text-slate-500">
-                    Higher complexity evaluates more strategies with more sophisticated models
+                    Caps the average risk score of the deposit at {riskBudgets[yieldParams.optimizationLevel]}, from 1 for lending to 5 for options writing
                   </p>
                 </div>
               </div>
@@ -567,6 +573,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     </span>
                   ) : 'Calculate Optimal Yield Strategy'}
                 </button>
+                {error && <p className="mt-3 text-sm text-red-400">{error}</p>}
               </div>
               
               <div className="mt-6 bg-slate-900/60 p-4 rounded-lg border border-slate-800">
@@ -609,7 +616,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     <path className="opacity-75" fill="currentColor" d="M4 12a8 8 0 018-8V0C5.373 0 0 5.373 0 12h4zm2 5.291A7.962 7.962 0 014 12H0c0 3.042 1.135 5.824 3 7.938l3-2.647z"></path>
                   </svg>
                   <p className="text-slate-300 mb-2">Optimizing Yield Strategy...</p>
-                  <p className="text-sm text-slate-500">Evaluating {yieldParams.optimizationLevel === 'low' ? 'basic' : yieldParams.optimizationLevel === 'medium' ? 'advanced' : 'complex'} strategies</p>
+                  <p className="text-sm text-slate-500">Allocating within a risk budget of {riskBudgets[yieldParams.optimizationLevel]}</p>
                 </div>
               ) : (
                 <div className="space-y-6">
@@ -664,7 +671,7 @@ const FinancialComputations: React.FC<FinancialComputationsProps> = ({ account }
                     <div>
                       <p className="text-xs font-medium text-amber-300 uppercase tracking-wider">Execution Stats</p>
                       <p className="text-sm text-slate-300">
-                        <span className="font-mono text-amber-400">{yieldResults.executionTime}ms</span> on-chain execution time
+                        <span className="font-mono text-amber-400">{yieldResults.executionTime}ms</span> round trip to the contract
                       </p>
                     </div>
                     <div className="bg-amber-900/30 px-3 py-1 rounded-full">
@@ -3,9 +3,10 @@ import { ethers } from 'ethers';
 
 // Define basic ABI for interaction with the contract
 const ArbiProofABI = [
-  "function initiate_dispute(bytes32 txHash, address defender) external payable returns (bytes32)",
-  "function submit_bisection_challenge(bytes32 disputeId, uint256 bisectionPoint, bytes32 claimHash) external",
-  "function resolve_dispute(bytes32 disputeId, bool challengerWon) external"
+  "function initiate_dispute(bytes32 assertionId, uint256 traceLength, bytes32 endStateHash, bytes32 traceRoot, bytes32[] startProof, bytes32[] endProof) external payable returns (bytes32)",
+  "function submit_bisection_challenge(bytes32 disputeId, uint256 bisectionPoint, bytes32 claimHash, bytes32[] claimProof, bytes32[] claimExpansion) external",
+  "function resolve_dispute(bytes32 disputeId) external",
+  "function get_dispute_outcome(bytes32 disputeId) external view returns (uint8 outcome, uint8 reason, address winner)"
 ];
 
 interface FraudProofSimulatorProps {
@@ -24,29 +24,29 @@ const ZkProofVerification: React.FC = () => {
     }
   };
 
-  // Sample proof data (actual ZK proofs are much larger)
+  // Proof from the contract's gas benchmark, in the word order verify_groth16
+  // takes: A (G1), B (G2, imaginary parts first), C (G1)
   const sampleProof = {
     pi_a: [
-      "0x2b418e0aaa2fa043af1fd1ad0e10ee21ea16f9b7cafc29942cce0b0c7b5c6551",
-      "0x1c5861ab00a3b4f1e19363c15af45a8598b3b32029c75019acbb72291eb18e8f"
+      "0x20c0a5faf4f62eec98f6a86d9e33a6225a3b0dc556a1601182a4510da7ba666c",
+      "0x09321ac7227a8c67a153d9e5f5cfc29aad8f6f2755c148b1066a2557d1294758"
     ],
     pi_b: [
       [
-        "0x0fe573dbe6cf41d7236d137563725045ead503796f903e7dcccee95c352afdb1",
-        "0x09050f118b018e927f54d06c99bace088f962060b672f8adba3283dc739bafac"
+        "0x198e9393920d483a7260bfb731fb5d25f1aa493335a9e71297e485b7aef312c2",
+        "0x1800deef121f1e76426a00665e5c4479674322d4f75edadd46debd5cd992f6ed"
       ],
       [
-        "0x2ce60cdb91946a9d78ac041b695e9a0878dad424aaefc3d695b5b7e98098bb2c",
-        "0x0da7e70e776c41773bcabecff4a37d37b5ce58c066add2d0f8e300e2902c1ec7"
+        "0x090689d0585ff075ec9e99ad690c3395bc4b313370b38ef355acdadcd122975b",
+        "0x12c85ea5db8c6deb4aab71808dcb408fe3d1e7690c43d37b4ce6cc0166fa7daa"
       ]
     ],
     pi_c: [
-      "0x192f7dc9f3b9f86155b46baa7279861547251b2a0f99ed071eb9c3d39b4aedd9",
-      "0x13a68d145a42e8c3273b5f4e45c77eb9101c5bb98dce23b4a92d2c669936e50c"
+      "0x2a14705537b009189da8808651eecdb82482477fe92ac12ca8b71f80fc3d49ef",
+      "0x2df7ee7f243ea8b38e1ddf14029258877a618c779fd4717db6177e19ea67ec38"
     ],
     inputs: [
-      "0x0000000000000000000000000000000000000000000000000000000000000001",
-      "0x0000000000000000000000000000000000000000000000000000000000000002"
+      "0x0000000000000000000000000000000000000000000000000000000000000007"
     ]
   };
 
@@ -130,7 +130,7 @@ const ZkProofVerification: React.FC = () => {
           <div className="flex justify-between items-start">
             <div>
               <h3 className="text-slate-400 text-sm font-medium uppercase tracking-wider">Proof Size</h3>
-              <p className="text-2xl font-bold text-white mt-1">298 bytes</p>
+              <p className="text-2xl font-bold text-white mt-1">256 bytes</p>
             </div>
             <div className="p-2 bg-blue-900/20 rounded-lg">
               <svg xmlns="http://www.w3.org/2000/svg" className="h-6 w-6 text-blue-400" fill="none" viewBox="0 0 24 24" stroke="currentColor">
@@ -138,7 +138,7 @@ const ZkProofVerification: React.FC = () => {
               </svg>
             </div>
           </div>
-          <div className="mt-2 text-sm text-slate-400">Compressed elliptic curve points</div>
+          <div className="mt-2 text-sm text-slate-400">Uncompressed BN254 curve points</div>
         </div>
         
         <div className="bg-slate-800/60 rounded-xl p-5 border border-slate-700">
@@ -202,7 +202,7 @@ const ZkProofVerification: React.FC = () => {
       <div className="bg-slate-900/60 rounded-xl border border-slate-800 overflow-hidden">
         <div className="bg-slate-800 px-6 py-3 font-mono text-slate-300 text-sm flex justify-between items-center">
           <span>Sample ZK Proof (Groth16)</span>
-          <span className="bg-slate-700 px-2 py-1 rounded text-xs">298 bytes</span>
+          <span className="bg-slate-700 px-2 py-1 rounded text-xs">256 bytes</span>
         </div>
         <pre className="p-6 font-mono text-xs text-slate-400 overflow-x-auto">
           {JSON.stringify(sampleProof, null, 2)}
@@ -1,8 +1,43 @@
 export const ArbiProofABI = [
-  "function initiate_dispute(bytes32 txHash, address defender) external payable returns (bytes32)",
-  "function submit_bisection_challenge(bytes32 disputeId, uint256 bisectionPoint, bytes32 claimHash) external",
-  "function resolve_dispute(bytes32 disputeId, bool challengerWon) external",
+  "function get_genesis_state_root() external view returns (bytes32)",
+  "function post_assertion(bytes32 parentId, bytes32 stateRoot) external payable returns (bytes32)",
+  "function confirm_assertion(bytes32 assertionId) external",
+  "function initiate_dispute(bytes32 assertionId, uint256 traceLength, bytes32 endStateHash, bytes32 traceRoot, bytes32[] startProof, bytes32[] endProof) external payable returns (bytes32)",
+  "function submit_bisection_challenge(bytes32 disputeId, uint256 bisectionPoint, bytes32 claimHash, bytes32[] claimProof, bytes32[] claimExpansion) external",
+  "function resolve_dispute(bytes32 disputeId) external",
+  "function cancel_dispute(bytes32 disputeId) external",
+  "function settle_mutually(bytes32 disputeId, address winner, uint256 deadline, bytes challengerSignature, bytes defenderSignature) external",
+  "function settlement_digest(bytes32 disputeId, address winner, uint256 deadline) external view returns (bytes32)",
+  "function get_dispute_outcome(bytes32 disputeId) external view returns (uint8 outcome, uint8 reason, address winner)",
   "function benchmark_comprehensive() external returns (string[] memory, uint256[] memory)",
+  "function compute_dispute_id(bytes32 assertionId, address challenger) external view returns (bytes32)",
+  "function get_assertion(bytes32 assertionId) external view returns (address proposer, bytes32 parentId, bytes32 stateRoot, uint256 bond, uint256 deadline, uint8 status, uint256 openDisputes)",
+  "function get_open_disputes(bytes32 assertionId) external view returns (bytes32[])",
   "function dispute_exists(bytes32 disputeId) external view returns (bool)",
-  "function hook_before_swap(bytes32 poolId, address sender, uint256 amountIn, address tokenIn) external returns (uint256)"
+  "function get_dispute_count() external view returns (uint256)",
+  "function get_disputes(uint256 offset, uint256 limit) external view returns (bytes32[])",
+  "function get_challenger_dispute_count(address challenger) external view returns (uint256)",
+  "function get_challenger_disputes(address challenger, uint256 offset, uint256 limit) external view returns (bytes32[])",
+  "function get_defender_dispute_count(address defender) external view returns (uint256)",
+  "function get_defender_disputes(address defender, uint256 offset, uint256 limit) external view returns (bytes32[])",
+  "function get_status_counts() external view returns (uint256 pending, uint256 inProgress, uint256 resolved)",
+  "function get_dispute(bytes32 disputeId) external view returns (address challenger, address defender, uint8 status, uint256 currentRound, uint256 totalRounds, uint256 timestamp, bytes32 assertionId, uint256 rangeStart, uint256 rangeEnd, bytes32 startHash, bytes32 challengerEndHash, bytes32 defenderEndHash, uint256 challengerStake, uint256 defenderStake, uint256 bondDeadline, uint256 clockBudget)",
+  "function get_challenge_round(bytes32 disputeId, uint256 round) external view returns (uint256 round, uint256 bisectionPoint, bytes32 challengerClaim, bytes32 defenderResponse, uint8 status)",
+  "function get_challenge_rounds(bytes32 disputeId) external view returns (tuple(uint256 round, uint256 bisectionPoint, bytes32 challengerClaim, bytes32 defenderResponse, uint8 status)[])",
+  "function register_verifying_key(uint256[] vk) external returns (bytes32)",
+  "function verify_groth16(bytes32 vkId, uint256[] proof, uint256[] publicInputs) external view returns (bool)",
+  "function verify_fri(uint8 logDegree, uint8 logBlowup, uint8 numQueries, bytes32[] roots, uint64 finalValue, uint64[] values, bytes32[] paths) external view returns (bool)",
+  "function benchmark_groth16(bytes32 vkId, uint256[] proof, uint256[] publicInputs) external view returns (bool valid, uint256 gasUsed)",
+  "function price_option(uint256 spot, uint256 strike, uint256 rate, uint256 vol, uint256 time, bool isCall) external view returns (uint256)",
+  "function option_greeks(uint256 spot, uint256 strike, uint256 rate, uint256 vol, uint256 time, bool isCall) external view returns (int128 delta, int128 gamma, int128 vega, int128 theta, int128 rho)",
+  "function implied_volatility(uint256 price, uint256 spot, uint256 strike, uint256 rate, uint256 time, bool isCall) external view returns (uint256)",
+  "function optimize_yield(uint256 deposit, uint256[] apys, uint256[] risks, uint256[] capacities, uint256 riskBudget) external view returns (uint256[] amounts, uint256 blendedApy)",
+  "function price_asian_option(uint256 spot, uint256 strike, uint256 rate, uint256 vol, uint256 time, bool isCall, uint32 paths, uint32 steps, bytes32 seed) external view returns (uint256 price, uint256 stdError)",
+  "function price_barrier_option(uint256 spot, uint256 strike, uint256 barrier, uint8 barrierType, uint256 rate, uint256 vol, uint256 time, bool isCall, uint32 paths, uint32 steps, bytes32 seed) external view returns (uint256 price, uint256 stdError)",
+  "function get_pool_volatility(bytes32 poolId) external view returns (uint256 volatility, uint256 fee)",
+  "function get_pool_observations(bytes32 poolId) external view returns (uint256[] prices, uint256[] timestamps)",
+  "function set_pool_manager(address poolManager) external",
+  "function get_pool_manager() external view returns (address)",
+  "function hook_before_swap(bytes32 poolId, address sender, uint256 amountIn, address tokenIn) external returns (uint256)",
+  "function hook_after_swap(bytes32 poolId, uint256 price) external"
 ];
\ No newline at end of file